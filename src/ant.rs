@@ -0,0 +1,119 @@
+// What an ant does on a cell of a given colour before moving forward
+#[derive(Clone, Copy, PartialEq)]
+pub enum Turn {
+    Left,
+    Right,
+    Straight, // N: keep going
+    Back,     // U: turn around
+}
+
+// Headings in clockwise order, starting from up
+const DIRECTIONS: [(i32, i32); 4] = [(0, -1), (1, 0), (0, 1), (-1, 0)];
+
+// An ant (or multi-colour turmite) on the board, heading 0..4 is up, right, down, left
+#[derive(Clone, Copy)]
+pub struct Ant {
+    pub x: usize,
+    pub y: usize,
+    pub heading: u8,
+}
+
+impl Ant {
+    pub fn direction(&self) -> (i32, i32) {
+        DIRECTIONS[self.heading as usize]
+    }
+}
+
+// Parse a rule string such as "RL" (Langton's ant) or "LLRR": letter i is the
+// turn taken on a cell of colour i, which then advances to colour i + 1
+pub fn parse_rule(rule: &str) -> Result<Vec<Turn>, String> {
+    let turns: Vec<Turn> = rule
+        .trim()
+        .chars()
+        .map(|c| match c.to_ascii_uppercase() {
+            'L' => Ok(Turn::Left),
+            'R' => Ok(Turn::Right),
+            'N' => Ok(Turn::Straight),
+            'U' => Ok(Turn::Back),
+            _ => Err(format!("'{}' is not a turn (use L, R, N or U)", c)),
+        })
+        .collect::<Result<_, _>>()?;
+    if turns.len() < 2 || turns.len() > 255 {
+        return Err(format!("rule '{}' should have between 2 and 255 turns", rule));
+    }
+    Ok(turns)
+}
+
+// Toroidal board of colours walked by one or more ants sharing a rule
+pub struct AntBoard {
+    cells: Vec<Vec<u8>>,
+    ants: Vec<Ant>,
+    rule: Vec<Turn>,
+    rule_string: String,
+}
+
+impl AntBoard {
+    // Empty board with the ants spread along the middle row, their headings
+    // rotating so several ants don't just march in parallel
+    pub fn new(rule_string: &str, ant_count: usize, width: usize, height: usize) -> Result<Self, String> {
+        let rule = parse_rule(rule_string)?;
+        let ants = (0..ant_count)
+            .map(|i| Ant { x: width * (2 * i + 1) / (2 * ant_count), y: height / 2, heading: (i % 4) as u8 })
+            .collect();
+        Ok(AntBoard {
+            cells: vec![vec![0; width]; height],
+            ants,
+            rule,
+            rule_string: rule_string.trim().to_ascii_uppercase(),
+        })
+    }
+
+    pub fn cells(&self) -> &[Vec<u8>] {
+        &self.cells
+    }
+
+    pub fn ants(&self) -> &[Ant] {
+        &self.ants
+    }
+
+    pub fn rule_string(&self) -> &str {
+        &self.rule_string
+    }
+
+    pub fn colors(&self) -> u8 {
+        self.rule.len() as u8
+    }
+
+    pub fn set_cell(&mut self, x: usize, y: usize, color: u8) {
+        self.cells[y][x] = color % self.colors();
+    }
+
+    // Back to an empty board with the ants at their starting positions
+    pub fn reset(&mut self) {
+        let height = self.cells.len();
+        let width = self.cells.first().map_or(0, |row| row.len());
+        let board = AntBoard::new(&self.rule_string, self.ants.len(), width, height)
+            .expect("the rule was valid when the board was built");
+        *self = board;
+    }
+
+    // Move every ant once: turn by the colour underneath, recolour the cell, step forward
+    pub fn step(&mut self) {
+        let height = self.cells.len() as i32;
+        let width = self.cells.first().map_or(0, |row| row.len()) as i32;
+        for ant in &mut self.ants {
+            let cell = &mut self.cells[ant.y][ant.x];
+            ant.heading = match self.rule[*cell as usize] {
+                Turn::Left => (ant.heading + 3) % 4,
+                Turn::Right => (ant.heading + 1) % 4,
+                Turn::Straight => ant.heading,
+                Turn::Back => (ant.heading + 2) % 4,
+            };
+            *cell = (*cell + 1) % self.rule.len() as u8;
+
+            let (dx, dy) = ant.direction();
+            ant.x = (ant.x as i32 + dx).rem_euclid(width) as usize;
+            ant.y = (ant.y as i32 + dy).rem_euclid(height) as usize;
+        }
+    }
+}
@@ -0,0 +1,312 @@
+use raylib::prelude::Color;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use crate::hensel::NEIGHBOR_OFFSETS;
+use crate::{BACKGROUND_COLOR, CUSTOM_COLOR, GENERATED_COLOR, OSCILLATOR_COLOR, SPACESHIP_COLOR, STILL_LIFE_COLOR};
+
+// Wireworld colours: electrons are blue heads with red tails running along yellow wire
+const WIRE_HEAD_COLOR: Color = Color::BLUE;
+const WIRE_TAIL_COLOR: Color = Color::RED;
+const CONDUCTOR_COLOR: Color = Color::YELLOW;
+
+// A cellular automaton over a toroidal grid of small integer states.
+// State 0 is always the empty background
+pub trait CellularAutomaton {
+    fn name(&self) -> &'static str;
+
+    fn states(&self) -> u8;
+
+    // Next state of a cell from its own state and its 8 neighbours (in NEIGHBOR_OFFSETS order)
+    fn next_state(&self, state: u8, neighbors: [u8; 8]) -> u8;
+
+    fn color(&self, state: u8) -> Color;
+
+    // State placed by the mouse
+    fn drawing_state(&self) -> u8 {
+        1
+    }
+
+    // Board shown at launch and after a reset; random boards draw from the seed,
+    // so the same seed gives the same start
+    fn initial_cells(&self, width: usize, height: usize, _seed: u64) -> Vec<Vec<u8>> {
+        vec![vec![0; width]; height]
+    }
+
+    // Advance a whole board one generation, wrapping around the edges
+    fn step(&self, cells: &[Vec<u8>]) -> Vec<Vec<u8>> {
+        let height = cells.len();
+        let width = cells.first().map_or(0, |row| row.len());
+        let mut next = vec![vec![0u8; width]; height];
+
+        for (y, next_row) in next.iter_mut().enumerate() {
+            for (x, next_cell) in next_row.iter_mut().enumerate() {
+                let mut neighbors = [0u8; 8];
+                for (neighbor, &(dx, dy)) in neighbors.iter_mut().zip(NEIGHBOR_OFFSETS.iter()) {
+                    let nx = (x as i32 + dx).rem_euclid(width as i32) as usize;
+                    let ny = (y as i32 + dy).rem_euclid(height as i32) as usize;
+                    *neighbor = cells[ny][nx];
+                }
+                *next_cell = self.next_state(cells[y][x], neighbors);
+            }
+        }
+        next
+    }
+}
+
+// Conway's Game of Life, B3/S23: state 1 is alive
+pub struct Conway;
+
+impl CellularAutomaton for Conway {
+    fn name(&self) -> &'static str {
+        "Life"
+    }
+
+    fn states(&self) -> u8 {
+        2
+    }
+
+    fn next_state(&self, state: u8, neighbors: [u8; 8]) -> u8 {
+        let live_neighbors = neighbors.iter().filter(|&&n| n == 1).count();
+        match (state, live_neighbors) {
+            (1, 2) | (_, 3) => 1,
+            _ => 0,
+        }
+    }
+
+    fn color(&self, state: u8) -> Color {
+        if state == 1 { GENERATED_COLOR } else { BACKGROUND_COLOR }
+    }
+}
+
+// Life where every live cell carries one of several colours (Immigration has 2,
+// QuadLife 4). Survivors keep their colour and a newborn takes the majority
+// colour of its three parents; in QuadLife, three parents of different colours
+// give the fourth colour
+pub struct ColoredLife {
+    name: &'static str,
+    colors: Vec<Color>,
+}
+
+impl ColoredLife {
+    pub fn immigration() -> Self {
+        ColoredLife { name: "Immigration", colors: vec![SPACESHIP_COLOR, CUSTOM_COLOR] }
+    }
+
+    pub fn quadlife() -> Self {
+        ColoredLife { name: "QuadLife", colors: vec![SPACESHIP_COLOR, CUSTOM_COLOR, OSCILLATOR_COLOR, STILL_LIFE_COLOR] }
+    }
+}
+
+impl CellularAutomaton for ColoredLife {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn states(&self) -> u8 {
+        self.colors.len() as u8 + 1
+    }
+
+    fn next_state(&self, state: u8, neighbors: [u8; 8]) -> u8 {
+        let parents: Vec<u8> = neighbors.iter().copied().filter(|&n| n != 0).collect();
+        match (state, parents.len()) {
+            (0, 3) => majority(&parents).unwrap_or_else(|| {
+                (1..self.states()).find(|color| !parents.contains(color)).unwrap_or(parents[0])
+            }),
+            (0, _) => 0,
+            (_, 2) | (_, 3) => state,
+            _ => 0,
+        }
+    }
+
+    fn color(&self, state: u8) -> Color {
+        match state {
+            0 => BACKGROUND_COLOR,
+            color => self.colors[(color as usize - 1) % self.colors.len()],
+        }
+    }
+
+    // A random soup in the middle of the board, every colour equally likely
+    fn initial_cells(&self, width: usize, height: usize, seed: u64) -> Vec<Vec<u8>> {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut cells = vec![vec![0; width]; height];
+        for row in cells.iter_mut().take(height * 3 / 4).skip(height / 4) {
+            for cell in row.iter_mut().take(width * 3 / 4).skip(width / 4) {
+                if rng.gen_bool(0.3) {
+                    *cell = rng.gen_range(1..self.states());
+                }
+            }
+        }
+        cells
+    }
+}
+
+// The value held by more than half of the parents, if any. Shared by the
+// multi-colour rules and by the optional colour inheritance of the Life grid
+pub fn majority<T: Copy + PartialEq>(parents: &[T]) -> Option<T> {
+    parents
+        .iter()
+        .copied()
+        .find(|&candidate| parents.iter().filter(|&&parent| parent == candidate).count() * 2 > parents.len())
+}
+
+// Wireworld: electrons (a head followed by a tail) travel along conductors,
+// which is enough to build wires, diodes and logic gates
+pub struct Wireworld;
+
+const EMPTY: u8 = 0;
+const HEAD: u8 = 1;
+const TAIL: u8 = 2;
+const CONDUCTOR: u8 = 3;
+
+impl CellularAutomaton for Wireworld {
+    fn name(&self) -> &'static str {
+        "Wireworld"
+    }
+
+    fn states(&self) -> u8 {
+        4
+    }
+
+    fn next_state(&self, state: u8, neighbors: [u8; 8]) -> u8 {
+        match state {
+            HEAD => TAIL,
+            TAIL => CONDUCTOR,
+            // A conductor fires when exactly one or two neighbouring heads touch it
+            CONDUCTOR => match neighbors.iter().filter(|&&n| n == HEAD).count() {
+                1 | 2 => HEAD,
+                _ => CONDUCTOR,
+            },
+            _ => EMPTY,
+        }
+    }
+
+    fn color(&self, state: u8) -> Color {
+        match state {
+            HEAD => WIRE_HEAD_COLOR,
+            TAIL => WIRE_TAIL_COLOR,
+            CONDUCTOR => CONDUCTOR_COLOR,
+            _ => BACKGROUND_COLOR,
+        }
+    }
+
+    fn drawing_state(&self) -> u8 {
+        CONDUCTOR
+    }
+
+    // Two clocks: a slow one driving a long wire and a fast one whose wire forks in two.
+    // The wires run to 10 cells from the right edge, and a circuit that doesn't fit
+    // the board is left out
+    fn initial_cells(&self, width: usize, height: usize, _seed: u64) -> Vec<Vec<u8>> {
+        let mut cells = vec![vec![EMPTY; width]; height];
+        let end = width.saturating_sub(10);
+        if end > 21 && height > 14 {
+            clock(&mut cells, 10, 10, 20, 14);
+            wire(&mut cells, 21, 12, end, 12);
+        }
+        if end > 61 && height > 38 {
+            clock(&mut cells, 10, 30, 16, 34);
+            wire(&mut cells, 17, 32, 60, 32);
+            wire(&mut cells, 60, 26, 60, 38);
+            wire(&mut cells, 61, 26, end, 26);
+            wire(&mut cells, 61, 38, end, 38);
+        }
+        cells
+    }
+}
+
+// Straight conductor between two cells in the same row or column
+fn wire(cells: &mut [Vec<u8>], x0: usize, y0: usize, x1: usize, y1: usize) {
+    for row in cells.iter_mut().take(y1 + 1).skip(y0) {
+        for cell in row.iter_mut().take(x1 + 1).skip(x0) {
+            *cell = CONDUCTOR;
+        }
+    }
+}
+
+// Rectangular conductor loop with one electron running clockwise around it;
+// the loop's length sets the clock period
+fn clock(cells: &mut [Vec<u8>], left: usize, top: usize, right: usize, bottom: usize) {
+    wire(cells, left, top, right, top);
+    wire(cells, left, bottom, right, bottom);
+    wire(cells, left, top, left, bottom);
+    wire(cells, right, top, right, bottom);
+    cells[top][left + 1] = TAIL;
+    cells[top][left + 2] = HEAD;
+}
+
+// Built-in automaton by name, for picking one at launch
+pub fn by_name(name: &str) -> Option<Box<dyn CellularAutomaton>> {
+    match name.to_ascii_lowercase().as_str() {
+        "life" | "conway" => Some(Box::new(Conway)),
+        "wireworld" => Some(Box::new(Wireworld)),
+        "immigration" => Some(Box::new(ColoredLife::immigration())),
+        "quadlife" => Some(Box::new(ColoredLife::quadlife())),
+        _ => None,
+    }
+}
+
+// A toroidal board stepped by any automaton
+pub struct AutomatonBoard {
+    automaton: Box<dyn CellularAutomaton>,
+    cells: Vec<Vec<u8>>,
+}
+
+impl AutomatonBoard {
+    pub fn new(automaton: Box<dyn CellularAutomaton>, width: usize, height: usize, seed: u64) -> Self {
+        let cells = automaton.initial_cells(width, height, seed);
+        AutomatonBoard { automaton, cells }
+    }
+
+    pub fn automaton(&self) -> &dyn CellularAutomaton {
+        self.automaton.as_ref()
+    }
+
+    pub fn cells(&self) -> &[Vec<u8>] {
+        &self.cells
+    }
+
+    pub fn set_cell(&mut self, x: usize, y: usize, state: u8) {
+        self.cells[y][x] = state;
+    }
+
+    // Back to the automaton's initial board for a seed
+    pub fn reset(&mut self, seed: u64) {
+        let height = self.cells.len();
+        let width = self.cells.first().map_or(0, |row| row.len());
+        self.cells = self.automaton.initial_cells(width, height, seed);
+    }
+
+    pub fn step(&mut self) {
+        self.cells = self.automaton.step(&self.cells);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wireworld_fits_small_boards() {
+        for (width, height) in [(1, 1), (12, 30), (31, 15), (40, 20), (72, 39), (100, 100)] {
+            let cells = Wireworld.initial_cells(width, height, 0);
+            assert_eq!((cells.len(), cells[0].len()), (height, width));
+        }
+        // Both circuits on the default board, each with one electron
+        let heads = Wireworld.initial_cells(100, 100, 0).iter().flatten().filter(|&&state| state == HEAD).count();
+        assert_eq!(heads, 2);
+    }
+
+    #[test]
+    fn colored_soups_replay_from_the_seed() {
+        let quadlife = ColoredLife::quadlife();
+        let start = quadlife.initial_cells(40, 30, 1234);
+        assert_eq!(quadlife.initial_cells(40, 30, 1234), start);
+        assert_ne!(quadlife.initial_cells(40, 30, 1235), start);
+        assert!(start.iter().flatten().any(|&state| state != 0));
+
+        let mut board = AutomatonBoard::new(Box::new(quadlife), 40, 30, 1234);
+        board.step();
+        board.reset(1234);
+        assert_eq!(board.cells(), start.as_slice());
+    }
+}
@@ -0,0 +1,413 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+// Longest period an object is run for while classifying it
+const MAX_PERIOD: usize = 64;
+// Objects growing past this many cells in isolation are left unclassified
+const MAX_POPULATION: usize = 400;
+// Shapes remembered before the classifier starts over, since soups keep making new ones
+const MAX_CACHED_SHAPES: usize = 100_000;
+// Generations looked back on when watching how objects behave
+const LOOKBACK: usize = 30;
+
+// Code shared by everything that has not settled into a still life, oscillator or spaceship
+pub const UNSETTLED: &str = "unsettled";
+
+// Well-known objects by apgcode, the canonical name apgsearch gives them
+const KNOWN_OBJECTS: [(&str, &str); 17] = [
+    ("xs4_33", "block"),
+    ("xs6_696", "beehive"),
+    ("xs7_2596", "loaf"),
+    ("xs5_253", "boat"),
+    ("xs6_356", "ship"),
+    ("xs4_252", "tub"),
+    ("xs8_6996", "pond"),
+    ("xs7_25ac", "long boat"),
+    ("xp2_7", "blinker"),
+    ("xp2_7e", "toad"),
+    ("xp2_318c", "beacon"),
+    ("xp3_co9nas0san9oczgoldlo0oldlogz1047210127401", "pulsar"),
+    ("xp15_4r4z4r4", "pentadecathlon"),
+    ("xq4_153", "glider"),
+    ("xq4_6frc", "LWSS"),
+    ("xq4_27dee6", "MWSS"),
+    ("xq4_27deee6", "HWSS"),
+];
+
+// How an object behaves when run on its own under B3/S23
+#[derive(Clone, Copy, PartialEq)]
+pub enum Behaviour {
+    Still,
+    Oscillator(usize),                             // Period
+    Spaceship { period: usize, dx: i64, dy: i64 }, // Moves (dx, dy) cells every period
+    Unsettled,                                     // Still changing, growing or dying
+}
+
+// Human name of an apgcode, for the known objects
+pub fn name(code: &str) -> Option<&'static str> {
+    KNOWN_OBJECTS.iter().find(|(known, _)| *known == code).map(|(_, name)| *name)
+}
+
+// Splits a Life board into objects and classifies them, remembering every
+// shape it has seen so repeated objects cost a lookup
+#[derive(Default)]
+pub struct Classifier {
+    cache: HashMap<Vec<(i64, i64)>, (String, Behaviour)>,
+}
+
+impl Classifier {
+    // All objects on a toroidal board, or on a view of the plane when it doesn't
+    // wrap. Cells closer than two cells apart belong to the same object, so the
+    // gaps inside spaceships like the LWSS don't split them
+    pub fn census(&mut self, grid: &[Vec<bool>], wraps: bool) -> Vec<String> {
+        components(grid, wraps).iter().map(|cells| self.classify(cells).0).collect()
+    }
+
+    // apgcode and behaviour of a set of cells on the plane
+    pub fn classify(&mut self, cells: &[(i64, i64)]) -> (String, Behaviour) {
+        let (shape, _) = normalize(cells);
+        if let Some(known) = self.cache.get(&shape) {
+            return known.clone();
+        }
+        let result = classify_shape(&shape);
+        if self.cache.len() >= MAX_CACHED_SHAPES {
+            self.cache.clear();
+        }
+        self.cache.insert(shape, result.clone());
+        result
+    }
+}
+
+// Census as text, one "apgcode count name" line per object kind
+pub fn report(counts: &[(String, usize)]) -> String {
+    counts
+        .iter()
+        .map(|(code, count)| match name(code) {
+            Some(name) => format!("{} {} {}\n", code, count, name),
+            None => format!("{} {}\n", code, count),
+        })
+        .collect()
+}
+
+// An object of the board being watched, with what it was seen doing
+pub struct SeenObject {
+    pub cells: Vec<(usize, usize)>,
+    pub centre: (f64, f64), // Mean cell position, wrapped onto the board
+    pub behaviour: Behaviour,
+}
+
+// Objects of one generation by shape, each shape with where its copies are
+type ShapeIndex = HashMap<Vec<(i64, i64)>, Vec<(i64, i64)>>;
+
+// Watches the objects of consecutive generations of a board and tells
+// how each one behaved: an object that had the same shape in the same place one
+// generation ago is still, t generations ago it oscillates with period t, and
+// nearby but shifted it is a spaceship
+#[derive(Default)]
+pub struct BehaviourTracker {
+    frames: VecDeque<ShapeIndex>, // Newest first
+    last_generation: Option<u64>,
+}
+
+impl BehaviourTracker {
+    // Objects of a new generation, as grid cells, with what they were seen doing,
+    // on a toroidal board or a view of the plane that doesn't wrap. A generation
+    // that doesn't follow the last one starts the watch over
+    pub fn observe(&mut self, generation: u64, grid: &[Vec<bool>], wraps: bool) -> Vec<SeenObject> {
+        if self.last_generation.map(|last| last + 1) != Some(generation) {
+            self.frames.clear();
+        }
+        self.last_generation = Some(generation);
+
+        let height = grid.len() as i64;
+        let width = grid.first().map_or(0, |row| row.len()) as i64;
+        // Shortest offset between two places on the torus
+        let wrap = |d: i64, size: i64| if wraps { (d + size / 2).rem_euclid(size) - size / 2 } else { d };
+
+        let mut index = ShapeIndex::new();
+        let mut objects = Vec::new();
+        for cells in components(grid, wraps) {
+            let (shape, position) = normalize(&cells);
+            let position = (position.0.rem_euclid(width), position.1.rem_euclid(height));
+            let behaviour = self
+                .frames
+                .iter()
+                .zip(1..)
+                .find_map(|(frame, age)| {
+                    let places = frame.get(&shape)?;
+                    let shifts = places.iter().map(|&(x, y)| (wrap(position.0 - x, width), wrap(position.1 - y, height)));
+                    if places.contains(&position) {
+                        Some(if age == 1 { Behaviour::Still } else { Behaviour::Oscillator(age) })
+                    } else {
+                        // Nothing moves faster than one cell per generation
+                        shifts
+                            .filter(|&(dx, dy)| dx.abs().max(dy.abs()) <= age as i64)
+                            .min_by_key(|&(dx, dy)| dx.abs() + dy.abs())
+                            .map(|(dx, dy)| Behaviour::Spaceship { period: age, dx, dy })
+                    }
+                })
+                .unwrap_or(Behaviour::Unsettled);
+
+            index.entry(shape).or_default().push(position);
+            let count = cells.len() as f64;
+            let centre = (
+                (cells.iter().map(|c| c.0 as f64).sum::<f64>() / count).rem_euclid(width as f64),
+                (cells.iter().map(|c| c.1 as f64).sum::<f64>() / count).rem_euclid(height as f64),
+            );
+            let cells = cells.iter().map(|&(x, y)| (x.rem_euclid(width) as usize, y.rem_euclid(height) as usize)).collect();
+            objects.push(SeenObject { cells, centre, behaviour });
+        }
+
+        self.frames.push_front(index);
+        self.frames.truncate(LOOKBACK);
+        objects
+    }
+}
+
+// Object counts by apgcode, most common first
+pub fn counts(codes: &[String]) -> Vec<(String, usize)> {
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for code in codes {
+        *counts.entry(code.as_str()).or_default() += 1;
+    }
+    let mut counts: Vec<(String, usize)> = counts.into_iter().map(|(code, count)| (code.to_string(), count)).collect();
+    counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    counts
+}
+
+// Groups of live cells, as positions on the plane unwrapped across the board's edges.
+// On a grid that doesn't wrap, cells past the edges are not neighbours
+fn components(grid: &[Vec<bool>], wraps: bool) -> Vec<Vec<(i64, i64)>> {
+    let height = grid.len() as i64;
+    let width = grid.first().map_or(0, |row| row.len()) as i64;
+    let mut seen = vec![vec![false; width as usize]; height as usize];
+    let mut objects = Vec::new();
+
+    for start_y in 0..height {
+        for start_x in 0..width {
+            if !grid[start_y as usize][start_x as usize] || seen[start_y as usize][start_x as usize] {
+                continue;
+            }
+            seen[start_y as usize][start_x as usize] = true;
+            let mut cells = Vec::new();
+            let mut queue = VecDeque::from([(start_x, start_y)]);
+            while let Some((x, y)) = queue.pop_front() {
+                cells.push((x, y));
+                for dy in -2..=2 {
+                    for dx in -2..=2 {
+                        let (nx, ny) = (x + dx, y + dy);
+                        let outside = !(0..width).contains(&nx) || !(0..height).contains(&ny);
+                        if !wraps && outside {
+                            continue;
+                        }
+                        let (gx, gy) = (nx.rem_euclid(width) as usize, ny.rem_euclid(height) as usize);
+                        if grid[gy][gx] && !seen[gy][gx] {
+                            seen[gy][gx] = true;
+                            queue.push_back((nx, ny));
+                        }
+                    }
+                }
+            }
+            objects.push(cells);
+        }
+    }
+    objects
+}
+
+// Cells moved so the bounding box starts at the origin, sorted, with the offset taken off
+fn normalize(cells: &[(i64, i64)]) -> (Vec<(i64, i64)>, (i64, i64)) {
+    let left = cells.iter().map(|c| c.0).min().unwrap_or(0);
+    let top = cells.iter().map(|c| c.1).min().unwrap_or(0);
+    let mut shape: Vec<(i64, i64)> = cells.iter().map(|&(x, y)| (x - left, y - top)).collect();
+    shape.sort_unstable();
+    (shape, (left, top))
+}
+
+// One B3/S23 generation on the unbounded plane
+fn life_step(cells: &HashSet<(i64, i64)>) -> HashSet<(i64, i64)> {
+    let mut neighbors: HashMap<(i64, i64), u8> = HashMap::new();
+    for &(x, y) in cells {
+        for dy in -1..=1 {
+            for dx in -1..=1 {
+                if dx != 0 || dy != 0 {
+                    *neighbors.entry((x + dx, y + dy)).or_default() += 1;
+                }
+            }
+        }
+    }
+    neighbors
+        .into_iter()
+        .filter(|&(cell, count)| count == 3 || (count == 2 && cells.contains(&cell)))
+        .map(|(cell, _)| cell)
+        .collect()
+}
+
+// Run a shape until it comes back, possibly shifted, and name it by the
+// smallest code over its phases and orientations
+fn classify_shape(shape: &[(i64, i64)]) -> (String, Behaviour) {
+    let mut phases = vec![shape.to_vec()];
+    let mut cells: HashSet<(i64, i64)> = shape.iter().copied().collect();
+    let mut behaviour = Behaviour::Unsettled;
+    for period in 1..=MAX_PERIOD {
+        cells = life_step(&cells);
+        if cells.is_empty() || cells.len() > MAX_POPULATION {
+            break;
+        }
+        let (next, (dx, dy)) = normalize(&cells.iter().copied().collect::<Vec<_>>());
+        if next == shape {
+            behaviour = match (period, dx, dy) {
+                (1, 0, 0) => Behaviour::Still,
+                (_, 0, 0) => Behaviour::Oscillator(period),
+                _ => Behaviour::Spaceship { period, dx, dy },
+            };
+            break;
+        }
+        phases.push(next);
+    }
+
+    let prefix = match behaviour {
+        Behaviour::Still => format!("xs{}", shape.len()),
+        Behaviour::Oscillator(period) => format!("xp{}", period),
+        Behaviour::Spaceship { period, .. } => format!("xq{}", period),
+        // Unsettled objects are counted together, their shapes mean little yet
+        Behaviour::Unsettled => return (UNSETTLED.to_string(), behaviour),
+    };
+    let body = phases
+        .iter()
+        .flat_map(|phase| (0..8).map(move |orientation| wechsler(&transform(phase, orientation))))
+        .min_by(|a, b| a.len().cmp(&b.len()).then_with(|| a.cmp(b)))
+        .unwrap_or_default();
+    (format!("{}_{}", prefix, body), behaviour)
+}
+
+// One of the 8 rotations and reflections of a shape, moved back to the origin
+fn transform(shape: &[(i64, i64)], orientation: u8) -> Vec<(i64, i64)> {
+    let cells: Vec<(i64, i64)> = shape
+        .iter()
+        .map(|&(x, y)| {
+            let (x, y) = if orientation & 4 != 0 { (y, x) } else { (x, y) };
+            let x = if orientation & 1 != 0 { -x } else { x };
+            let y = if orientation & 2 != 0 { -y } else { y };
+            (x, y)
+        })
+        .collect();
+    normalize(&cells).0
+}
+
+// Extended Wechsler format: rows in strips of 5, each column of a strip as one
+// base-32 digit (top row is the lowest bit), runs of empty columns shortened to
+// w (2), x (3) or y plus a digit (4 to 39), strips separated by z
+fn wechsler(shape: &[(i64, i64)]) -> String {
+    const DIGITS: &[u8] = b"0123456789abcdefghijklmnopqrstuvwxyz";
+    let width = shape.iter().map(|c| c.0 + 1).max().unwrap_or(0);
+    let height = shape.iter().map(|c| c.1 + 1).max().unwrap_or(0);
+    let cells: HashSet<(i64, i64)> = shape.iter().copied().collect();
+
+    let mut strips = Vec::new();
+    for strip in 0..(height + 4) / 5 {
+        let mut columns: Vec<u8> = (0..width)
+            .map(|x| (0..5).filter(|&row| cells.contains(&(x, strip * 5 + row))).fold(0, |digit, row| digit | 1 << row))
+            .collect();
+        while columns.last() == Some(&0) {
+            columns.pop();
+        }
+
+        let mut text = String::new();
+        let mut zeros = 0;
+        for column in columns.into_iter().chain(std::iter::once(u8::MAX)) {
+            if column == 0 {
+                zeros += 1;
+                continue;
+            }
+            while zeros > 0 {
+                let run: usize = zeros.min(39);
+                match run {
+                    1 => text.push('0'),
+                    2 => text.push('w'),
+                    3 => text.push('x'),
+                    _ => {
+                        text.push('y');
+                        text.push(DIGITS[run - 4] as char);
+                    },
+                }
+                zeros -= run;
+            }
+            if column != u8::MAX {
+                text.push(DIGITS[column as usize] as char);
+            }
+        }
+        strips.push(text);
+    }
+    strips.join("z")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A grid with live cells at some points
+    fn grid(width: usize, height: usize, cells: &[(usize, usize)]) -> Vec<Vec<bool>> {
+        let mut grid = vec![vec![false; width]; height];
+        for &(x, y) in cells {
+            grid[y][x] = true;
+        }
+        grid
+    }
+
+    // Live cells of a picture drawn with 'O'
+    fn cells(rows: &[&str]) -> Vec<(i64, i64)> {
+        let mut cells = Vec::new();
+        for (y, row) in rows.iter().enumerate() {
+            for (x, c) in row.chars().enumerate() {
+                if c == 'O' {
+                    cells.push((x as i64, y as i64));
+                }
+            }
+        }
+        cells
+    }
+
+    #[test]
+    fn spaceships_are_named() {
+        let ships = [
+            ("LWSS", cells(&[".O..O", "O....", "O...O", "OOOO."])),
+            ("MWSS", cells(&["...O..", ".O...O", "O.....", "O....O", "OOOOO."])),
+            ("HWSS", cells(&["...OO..", ".O....O", "O......", "O.....O", "OOOOOO."])),
+        ];
+        let mut classifier = Classifier::default();
+        for (expected, ship) in ships {
+            let (code, behaviour) = classifier.classify(&ship);
+            assert_eq!(name(&code), Some(expected), "{} came out as {}", expected, code);
+            assert!(matches!(behaviour, Behaviour::Spaceship { period: 4, .. }));
+        }
+    }
+
+    #[test]
+    fn edges_join_objects_only_when_wrapping() {
+        // A block split by the left and right edges
+        let split = grid(10, 10, &[(0, 4), (0, 5), (9, 4), (9, 5)]);
+        assert_eq!(components(&split, true).len(), 1);
+        assert_eq!(components(&split, false).len(), 2);
+
+        let mut classifier = Classifier::default();
+        assert_eq!(classifier.census(&split, true), vec!["xs4_33".to_string()]);
+    }
+
+    #[test]
+    fn gliders_leaving_a_view_are_not_joined_across_it() {
+        // A glider heading down and right towards the right edge of a view, and a
+        // block on the left edge it would touch if the view wrapped
+        let mut board = crate::sparse::SparseBoard::new();
+        for (x, y) in [(15, 2), (16, 3), (14, 4), (15, 4), (16, 4), (0, 3), (0, 4), (1, 3), (1, 4)] {
+            board.set(x, y, true);
+        }
+        let mut tracker = BehaviourTracker::default();
+        let mut objects = Vec::new();
+        for generation in 0..=4 {
+            objects = tracker.observe(generation, &board.window(0, 0, 18, 10), false);
+            board.step();
+        }
+        let mut behaviours: Vec<_> = objects.iter().map(|object| object.behaviour).collect();
+        behaviours.sort_by_key(|behaviour| matches!(behaviour, Behaviour::Spaceship { .. }));
+        assert!(matches!(behaviours[..], [Behaviour::Still, Behaviour::Spaceship { period: 4, dx: 1, dy: 1 }]));
+    }
+}
@@ -0,0 +1,106 @@
+use std::collections::{HashMap, VecDeque};
+
+// A cycle the board has entered: it repeats every `period` generations from
+// generation `start` on. Period 1 is a still board
+#[derive(Clone, Copy)]
+pub struct Cycle {
+    pub period: u64,
+    pub start: u64,
+}
+
+// Watches the board hash of consecutive generations and reports the first one
+// seen before. Only a bounded window of hashes is kept, so longer periods go unnoticed
+pub struct CycleDetector {
+    first_seen: HashMap<u64, u64>, // Board hash to the generation it first appeared in
+    order: VecDeque<(u64, u64)>,   // (generation, hash) in the order they were seen
+    capacity: usize,
+    cycle: Option<Cycle>,
+}
+
+impl CycleDetector {
+    pub fn new(capacity: usize) -> Self {
+        CycleDetector { first_seen: HashMap::new(), order: VecDeque::new(), capacity: capacity.max(1), cycle: None }
+    }
+
+    pub fn clear(&mut self) {
+        self.first_seen.clear();
+        self.order.clear();
+        self.cycle = None;
+    }
+
+    pub fn cycle(&self) -> Option<Cycle> {
+        self.cycle
+    }
+
+    // Record the board of a generation; returns the cycle when this board closes
+    // one for the first time. Anything other than the generation after the last
+    // one (a reset, a rewind, a jump) starts the watch over, and so does a board
+    // that changed without a step, like an edit
+    pub fn observe(&mut self, generation: u64, hash: u64) -> Option<Cycle> {
+        match self.order.back() {
+            Some(&(last, last_hash)) if last == generation && last_hash == hash => return None,
+            Some(&(last, _)) if last + 1 == generation => {},
+            _ => self.clear(),
+        }
+
+        if let (None, Some(&start)) = (self.cycle, self.first_seen.get(&hash)) {
+            self.cycle = Some(Cycle { period: generation - start, start });
+            self.order.push_back((generation, hash));
+            return self.cycle;
+        }
+
+        self.first_seen.entry(hash).or_insert(generation);
+        self.order.push_back((generation, hash));
+        while self.order.len() > self.capacity {
+            if let Some((old_generation, old_hash)) = self.order.pop_front()
+                && self.first_seen.get(&old_hash) == Some(&old_generation)
+            {
+                self.first_seen.remove(&old_hash);
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn period_two_from_its_start() {
+        let mut detector = CycleDetector::new(100);
+        // Two transient boards, then a blinker flipping between hashes 20 and 21
+        for (generation, hash) in [(0, 10), (1, 11), (2, 20), (3, 21)] {
+            assert!(detector.observe(generation, hash).is_none());
+        }
+        let cycle = detector.observe(4, 20).unwrap();
+        assert_eq!((cycle.period, cycle.start), (2, 2));
+        // Reported once; the cycle stays known
+        assert!(detector.observe(5, 21).is_none());
+        assert_eq!(detector.cycle().map(|cycle| cycle.period), Some(2));
+    }
+
+    #[test]
+    fn edits_start_the_watch_over() {
+        let mut detector = CycleDetector::new(100);
+        for (generation, hash) in [(0, 20), (1, 21)] {
+            detector.observe(generation, hash);
+        }
+        // The same board seen again in the same generation is ignored
+        assert!(detector.observe(1, 21).is_none());
+        // An edit changes the board without a step, so hash 20 is forgotten
+        assert!(detector.observe(1, 30).is_none());
+        assert!(detector.observe(2, 20).is_none());
+        assert!(detector.cycle().is_none());
+        let cycle = detector.observe(3, 30).unwrap();
+        assert_eq!((cycle.period, cycle.start), (2, 1));
+    }
+
+    #[test]
+    fn short_windows_miss_long_periods() {
+        let mut detector = CycleDetector::new(2);
+        for generation in 0..10 {
+            assert!(detector.observe(generation, generation % 3).is_none());
+        }
+    }
+}
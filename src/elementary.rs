@@ -0,0 +1,126 @@
+use std::collections::VecDeque;
+use std::fmt;
+
+// A one-dimensional two-state rule
+#[derive(Clone, Copy, PartialEq)]
+pub enum Rule1D {
+    // Wolfram code 0-255: bit (left << 2 | centre << 1 | right) is the new state
+    Elementary(u8),
+    // Bit s of the code is the new state when the 2r + 1 cells around a cell hold s live cells
+    Totalistic { radius: u32, code: u64 },
+}
+
+impl Rule1D {
+    // Parse "30" or "W30" (elementary, Wolfram numbering) or "R2,T52" (totalistic code 52 of radius 2)
+    pub fn parse(rule: &str) -> Result<Self, String> {
+        let rule = rule.trim();
+        if let Ok(number) = rule.strip_prefix('W').unwrap_or(rule).parse::<u32>() {
+            return u8::try_from(number)
+                .map(Rule1D::Elementary)
+                .map_err(|_| format!("elementary rule {} should be between 0 and 255", number));
+        }
+
+        let mut radius = 1;
+        let mut code = None;
+        for token in rule.split(',') {
+            let value = token.get(1..).unwrap_or("");
+            match token.chars().next() {
+                Some('R') => radius = value.parse().map_err(|_| format!("'{}' is not a radius", value))?,
+                Some('T') => code = Some(value.parse::<u64>().map_err(|_| format!("'{}' is not a totalistic code", value))?),
+                _ => return Err(format!("unexpected field '{}' in rule '{}'", token, rule)),
+            }
+        }
+        // Sums run from 0 to 2r + 1, so the code needs 2r + 2 bits
+        if radius == 0 || radius > 31 {
+            return Err(format!("radius {} should be between 1 and 31", radius));
+        }
+        let code = code.ok_or(format!("rule '{}' is missing its T code", rule))?;
+        if radius < 31 && code >> (2 * radius + 2) != 0 {
+            return Err(format!("code {} is too large for radius {}", code, radius));
+        }
+        Ok(Rule1D::Totalistic { radius, code })
+    }
+
+    // Next row of a ring of cells
+    pub fn apply(&self, row: &[bool]) -> Vec<bool> {
+        let width = row.len() as i64;
+        let cell = |x: i64| row[x.rem_euclid(width) as usize] as u64;
+        (0..width)
+            .map(|x| match *self {
+                Rule1D::Elementary(number) => {
+                    let index = cell(x - 1) << 2 | cell(x) << 1 | cell(x + 1);
+                    number >> index & 1 == 1
+                },
+                Rule1D::Totalistic { radius, code } => {
+                    let radius = radius as i64;
+                    let sum: u64 = (x - radius..=x + radius).map(cell).sum();
+                    code >> sum & 1 == 1
+                },
+            })
+            .collect()
+    }
+}
+
+impl fmt::Display for Rule1D {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Rule1D::Elementary(number) => write!(f, "W{}", number),
+            Rule1D::Totalistic { radius, code } => write!(f, "R{},T{}", radius, code),
+        }
+    }
+}
+
+// Space-time diagram of a 1D automaton: the last `height` generations, oldest first.
+// Each step appends a row at the bottom and drops the top one, so the picture scrolls up
+pub struct SpaceTime {
+    rule: Rule1D,
+    rows: VecDeque<Vec<bool>>,
+    height: usize,
+}
+
+impl SpaceTime {
+    // Start from a single live cell in the middle of an otherwise empty row
+    pub fn new(rule: Rule1D, width: usize, height: usize) -> Self {
+        let mut first = vec![false; width];
+        if width > 0 {
+            first[width / 2] = true;
+        }
+        SpaceTime { rule, rows: VecDeque::from([first]), height: height.max(1) }
+    }
+
+    pub fn rule(&self) -> Rule1D {
+        self.rule
+    }
+
+    pub fn rows(&self) -> &VecDeque<Vec<bool>> {
+        &self.rows
+    }
+
+    // Width and height of the diagram, however many generations it has so far
+    pub fn size(&self) -> (usize, usize) {
+        (self.rows.back().map_or(0, |row| row.len()), self.height)
+    }
+
+    // The newest generation, which the next one is computed from
+    pub fn set_cell(&mut self, x: usize, alive: bool) {
+        if let Some(row) = self.rows.back_mut() {
+            row[x] = alive;
+        }
+    }
+
+    pub fn reset(&mut self) {
+        let width = self.rows.back().map_or(0, |row| row.len());
+        *self = SpaceTime::new(self.rule, width, self.height);
+    }
+
+    pub fn step(&mut self) {
+        let next = match self.rows.back() {
+            Some(row) => self.rule.apply(row),
+            None => return,
+        };
+        self.rows.push_back(next);
+        while self.rows.len() > self.height {
+            self.rows.pop_front();
+        }
+    }
+}
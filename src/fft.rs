@@ -0,0 +1,116 @@
+use std::f64::consts::PI;
+use std::ops::{Add, Mul, Sub};
+
+#[derive(Clone, Copy, Default)]
+pub struct Complex {
+    pub re: f64,
+    pub im: f64,
+}
+
+impl Complex {
+    pub fn new(re: f64, im: f64) -> Self {
+        Complex { re, im }
+    }
+}
+
+impl Add for Complex {
+    type Output = Complex;
+    fn add(self, other: Complex) -> Complex {
+        Complex::new(self.re + other.re, self.im + other.im)
+    }
+}
+
+impl Sub for Complex {
+    type Output = Complex;
+    fn sub(self, other: Complex) -> Complex {
+        Complex::new(self.re - other.re, self.im - other.im)
+    }
+}
+
+impl Mul for Complex {
+    type Output = Complex;
+    fn mul(self, other: Complex) -> Complex {
+        Complex::new(self.re * other.re - self.im * other.im, self.re * other.im + self.im * other.re)
+    }
+}
+
+// Radix-2 FFT over square power-of-two grids, with the twiddle factors computed once
+pub struct Fft2d {
+    size: usize,
+    twiddles: Vec<Complex>, // exp(-2*pi*i*k/size) for k < size/2
+}
+
+impl Fft2d {
+    pub fn new(size: usize) -> Self {
+        assert!(size.is_power_of_two(), "FFT size must be a power of two");
+        let twiddles = (0..size / 2)
+            .map(|k| {
+                let angle = -2.0 * PI * k as f64 / size as f64;
+                Complex::new(angle.cos(), angle.sin())
+            })
+            .collect();
+        Fft2d { size, twiddles }
+    }
+
+    // Transform a size x size row-major grid in place: every row, then every column.
+    // The inverse transform includes the 1/size^2 normalisation
+    pub fn transform(&self, data: &mut [Complex], inverse: bool) {
+        let n = self.size;
+        for row in data.chunks_mut(n) {
+            self.transform_1d(row, inverse);
+        }
+
+        let mut column = vec![Complex::default(); n];
+        for x in 0..n {
+            for (y, value) in column.iter_mut().enumerate() {
+                *value = data[y * n + x];
+            }
+            self.transform_1d(&mut column, inverse);
+            for (y, value) in column.iter().enumerate() {
+                data[y * n + x] = *value;
+            }
+        }
+
+        if inverse {
+            let scale = 1.0 / (n * n) as f64;
+            for value in data.iter_mut() {
+                *value = Complex::new(value.re * scale, value.im * scale);
+            }
+        }
+    }
+
+    // Iterative Cooley-Tukey: bit-reversal permutation, then butterflies of growing length
+    fn transform_1d(&self, data: &mut [Complex], inverse: bool) {
+        let n = data.len();
+        let mut j = 0;
+        for i in 1..n {
+            let mut bit = n >> 1;
+            while j & bit != 0 {
+                j ^= bit;
+                bit >>= 1;
+            }
+            j |= bit;
+            if i < j {
+                data.swap(i, j);
+            }
+        }
+
+        let mut len = 2;
+        while len <= n {
+            let stride = n / len;
+            for start in (0..n).step_by(len) {
+                for k in 0..len / 2 {
+                    let mut twiddle = self.twiddles[k * stride];
+                    if inverse {
+                        twiddle.im = -twiddle.im;
+                    }
+                    let even = data[start + k];
+                    let odd = data[start + k + len / 2] * twiddle;
+                    data[start + k] = even + odd;
+                    data[start + k + len / 2] = even - odd;
+                }
+            }
+            len <<= 1;
+        }
+    }
+}
@@ -0,0 +1,396 @@
+use std::fmt;
+use crate::hensel::{self, NEIGHBOR_OFFSETS};
+use crate::neighborhood::{self, Neighborhood};
+
+// A Generations rule: birth/survival conditions plus a number of states.
+// State 0 is dead and 1 is alive; a live cell that fails to survive goes through
+// the refractory states 2..states before dying, and those neither count as
+// neighbours nor can be born into.
+// For the classic range 1 Moore neighbourhood the conditions are indexed by the
+// neighbourhood mask, so isotropic non-totalistic rules like "B2-a/S12" fit too;
+// for every other neighbourhood they are indexed by the (weighted) neighbour count
+#[derive(Clone, PartialEq)]
+pub struct GenerationsRule {
+    pub birth: Vec<bool>,
+    pub survival: Vec<bool>,
+    pub states: u8,
+    pub neighborhood: Neighborhood,
+    pub include_centre: bool, // Whether a live cell counts itself (the M flag of HROT rules)
+}
+
+impl GenerationsRule {
+    // Parse a rule string in one of these notations:
+    // - "S/B/C", e.g. "345/2/4" for Star Wars
+    // - "B/S/C", e.g. "B2/S/C3" for Brian's Brain or "B2-a/S12" with Hensel letters.
+    //   The state count is optional, so a Life-like rule such as "B36/S23" is a
+    //   2-state Generations rule, and a trailing H or V switches to the range 1
+    //   hexagonal or von Neumann neighbourhood, e.g. "B2/S34H"
+    // - HROT, e.g. "R5,C2,M1,S34-58,B34-45,NM" for Larger than Life, where N is
+    //   M (Moore), N (von Neumann), H (hexagonal) or W plus one hex weight per cell
+    pub fn parse(rule: &str) -> Result<Self, String> {
+        let rule = rule.trim();
+        if rule.starts_with('R') && rule.contains(',') {
+            return parse_hrot(rule);
+        }
+
+        let (body, neighborhood) = match rule.strip_suffix('H') {
+            Some(body) => (body, Neighborhood::Hexagonal(1)),
+            None => match rule.strip_suffix('V') {
+                Some(body) => (body, Neighborhood::VonNeumann(1)),
+                None => (rule, Neighborhood::Moore(1)),
+            },
+        };
+        let classic = neighborhood == Neighborhood::Moore(1);
+        let max_sum = neighborhood.max_sum();
+        let conditions = |spec: &str| -> Result<Vec<bool>, String> {
+            if classic {
+                Ok(hensel::parse_conditions(spec)?.to_vec())
+            } else {
+                parse_counts(spec, max_sum)
+            }
+        };
+
+        let parts: Vec<&str> = body.split('/').collect();
+        if parts.len() < 2 || parts.len() > 3 {
+            return Err(format!("rule '{}' should have 2 or 3 parts separated by '/'", rule));
+        }
+
+        let mut birth = conditions("")?;
+        let mut survival = conditions("")?;
+        let mut states = 2;
+
+        let lettered = parts
+            .iter()
+            .any(|part| part.starts_with(|c: char| c.is_ascii_alphabetic()));
+        if lettered {
+            for part in &parts {
+                let mut chars = part.chars();
+                match chars.next().map(|c| c.to_ascii_uppercase()) {
+                    Some('B') => birth = conditions(chars.as_str())?,
+                    Some('S') => survival = conditions(chars.as_str())?,
+                    Some('C') | Some('G') => states = parse_states(chars.as_str())?,
+                    _ => return Err(format!("unexpected part '{}' in rule '{}'", part, rule)),
+                }
+            }
+        } else {
+            survival = conditions(parts[0])?;
+            birth = conditions(parts[1])?;
+            if let Some(count) = parts.get(2) {
+                states = parse_states(count)?;
+            }
+        }
+
+        Ok(GenerationsRule { birth, survival, states, neighborhood, include_centre: false })
+    }
+
+    // Whether the conditions are indexed by neighbourhood mask rather than by count
+    pub fn uses_masks(&self) -> bool {
+        self.neighborhood == Neighborhood::Moore(1) && !self.include_centre
+    }
+}
+
+impl fmt::Display for GenerationsRule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let short_suffix = match self.neighborhood {
+            Neighborhood::VonNeumann(1) => Some("V"),
+            Neighborhood::Hexagonal(1) => Some("H"),
+            _ => None,
+        };
+
+        if self.uses_masks() {
+            let birth: [bool; 256] = self.birth.as_slice().try_into().unwrap();
+            let survival: [bool; 256] = self.survival.as_slice().try_into().unwrap();
+            write!(f, "B{}/S{}", hensel::format_conditions(&birth), hensel::format_conditions(&survival))?;
+        } else if let (Some(_), false) = (short_suffix, self.include_centre) {
+            write!(f, "B{}/S{}", format_digits(&self.birth), format_digits(&self.survival))?;
+        } else {
+            return write!(
+                f,
+                "R{},C{},M{},S{},B{},N{}",
+                self.neighborhood.range(),
+                self.states,
+                self.include_centre as u8,
+                format_ranges(&self.survival),
+                format_ranges(&self.birth),
+                self.neighborhood,
+            );
+        }
+
+        if self.states > 2 {
+            write!(f, "/C{}", self.states)?;
+        }
+        if let Some(suffix) = short_suffix {
+            write!(f, "{}", suffix)?;
+        }
+        Ok(())
+    }
+}
+
+// Parse an HROT rule: comma separated R (range), C (states), M (count the centre),
+// S and B (lists of counts or ranges like "2-5") and N (neighbourhood) fields
+fn parse_hrot(rule: &str) -> Result<GenerationsRule, String> {
+    let mut range = 1;
+    let mut states = 2;
+    let mut include_centre = false;
+    let mut neighborhood_spec = "M";
+    let mut birth_items = Vec::new();
+    let mut survival_items = Vec::new();
+    let mut in_birth = false;
+
+    for token in rule.split(',') {
+        let value = token.get(1..).unwrap_or("");
+        match token.chars().next() {
+            Some('R') => range = value.parse().map_err(|_| format!("'{}' is not a range", value))?,
+            // C0 and C1 both mean a plain 2-state rule
+            Some('C') => states = parse_states(value).or_else(|err| match value {
+                "0" | "1" => Ok(2),
+                _ => Err(err),
+            })?,
+            Some('M') => include_centre = value == "1",
+            Some('S') => {
+                in_birth = false;
+                survival_items.extend(Some(value).filter(|item| !item.is_empty()));
+            },
+            Some('B') => {
+                in_birth = true;
+                birth_items.extend(Some(value).filter(|item| !item.is_empty()));
+            },
+            Some('N') => neighborhood_spec = value,
+            // Bare numbers continue the last S or B list
+            Some(c) if c.is_ascii_digit() => {
+                if in_birth { birth_items.push(token) } else { survival_items.push(token) }
+            },
+            _ => return Err(format!("unexpected field '{}' in rule '{}'", token, rule)),
+        }
+    }
+
+    if range == 0 || range > 50 {
+        return Err(format!("range {} should be between 1 and 50", range));
+    }
+    let neighborhood = neighborhood::parse_neighborhood(neighborhood_spec, range)?;
+    let include_centre = include_centre && !matches!(neighborhood, Neighborhood::Weighted(_));
+    let max_sum = neighborhood.max_sum() + include_centre as u32;
+
+    let mut rule = GenerationsRule {
+        birth: parse_ranges(&birth_items, max_sum)?,
+        survival: parse_ranges(&survival_items, max_sum)?,
+        states,
+        neighborhood,
+        include_centre,
+    };
+
+    // A range 1 Moore rule is an ordinary Life-like rule, stored by mask
+    if rule.uses_masks() {
+        let by_mask = |counts: &[bool]| (0..256).map(|mask: u32| counts[mask.count_ones() as usize]).collect();
+        rule.birth = by_mask(&rule.birth);
+        rule.survival = by_mask(&rule.survival);
+    }
+    Ok(rule)
+}
+
+// Single-digit counts, for the short notation of small neighbourhoods
+fn parse_counts(digits: &str, max_sum: u32) -> Result<Vec<bool>, String> {
+    let mut counts = vec![false; max_sum as usize + 1];
+    for c in digits.chars() {
+        match c.to_digit(10) {
+            Some(n) if n <= max_sum => counts[n as usize] = true,
+            _ => return Err(format!("'{}' is not a neighbour count between 0 and {}", c, max_sum)),
+        }
+    }
+    Ok(counts)
+}
+
+fn format_digits(counts: &[bool]) -> String {
+    (0..counts.len()).filter(|&n| counts[n]).map(|n| n.to_string()).collect()
+}
+
+// HROT count lists: single counts or inclusive ranges written "a-b" (or "a..b")
+fn parse_ranges(items: &[&str], max_sum: u32) -> Result<Vec<bool>, String> {
+    let mut counts = vec![false; max_sum as usize + 1];
+    for item in items {
+        let (low, high) = match item.split_once("..").or_else(|| item.split_once('-')) {
+            Some((low, high)) => (low, high),
+            None => (*item, *item),
+        };
+        let parse = |n: &str| n.parse::<u32>().map_err(|_| format!("'{}' is not a count", item));
+        let (low, high) = (parse(low)?, parse(high)?);
+        if low > high || high > max_sum {
+            return Err(format!("'{}' should be a count range within 0 to {}", item, max_sum));
+        }
+        for n in low..=high {
+            counts[n as usize] = true;
+        }
+    }
+    Ok(counts)
+}
+
+fn format_ranges(counts: &[bool]) -> String {
+    let mut items = Vec::new();
+    let mut n = 0;
+    while n < counts.len() {
+        if !counts[n] {
+            n += 1;
+            continue;
+        }
+        let start = n;
+        while n + 1 < counts.len() && counts[n + 1] {
+            n += 1;
+        }
+        items.push(if start == n { start.to_string() } else { format!("{}-{}", start, n) });
+        n += 1;
+    }
+    items.join(",")
+}
+
+fn parse_states(count: &str) -> Result<u8, String> {
+    match count.parse::<u8>() {
+        Ok(states) if states >= 2 => Ok(states),
+        _ => Err(format!("'{}' is not a state count between 2 and 255", count)),
+    }
+}
+
+// Toroidal N-state board stepped by a Generations rule
+pub struct GenerationsBoard {
+    cells: Vec<Vec<u8>>,
+    rule: GenerationsRule,
+}
+
+impl GenerationsBoard {
+    // Start from a Life board: live cells become state 1, everything else is dead
+    pub fn from_grid(grid: &[Vec<bool>], rule: GenerationsRule) -> Self {
+        let cells = grid
+            .iter()
+            .map(|row| row.iter().map(|&alive| alive as u8).collect())
+            .collect();
+        GenerationsBoard { cells, rule }
+    }
+
+    pub fn cells(&self) -> &[Vec<u8>] {
+        &self.cells
+    }
+
+    pub fn set_cell(&mut self, x: usize, y: usize, state: u8) {
+        self.cells[y][x] = state;
+    }
+
+    pub fn rule(&self) -> &GenerationsRule {
+        &self.rule
+    }
+
+    // Advance one generation, wrapping around the edges like update_grid
+    pub fn step(&mut self) {
+        let height = self.cells.len();
+        let width = self.cells.first().map_or(0, |row| row.len());
+        let mut next = vec![vec![0u8; width]; height];
+
+        // Larger neighbourhoods are counted all at once with a summed-area table
+        let sums = (!self.rule.uses_masks()).then(|| {
+            let alive: Vec<Vec<bool>> = self.cells.iter().map(|row| row.iter().map(|&state| state == 1).collect()).collect();
+            neighborhood::neighbor_sums(&alive, &self.rule.neighborhood, self.rule.include_centre)
+        });
+
+        for (y, next_row) in next.iter_mut().enumerate() {
+            for (x, next_cell) in next_row.iter_mut().enumerate() {
+                // Only fully alive cells count as neighbours
+                let index = match &sums {
+                    Some(sums) => sums[y][x] as usize,
+                    None => {
+                        let mut mask = 0usize;
+                        for (bit, &(dx, dy)) in NEIGHBOR_OFFSETS.iter().enumerate() {
+                            let nx = (x as i32 + dx).rem_euclid(width as i32) as usize;
+                            let ny = (y as i32 + dy).rem_euclid(height as i32) as usize;
+                            if self.cells[ny][nx] == 1 {
+                                mask |= 0x80 >> bit;
+                            }
+                        }
+                        mask
+                    },
+                };
+
+                *next_cell = match self.cells[y][x] {
+                    0 if self.rule.birth[index] => 1,
+                    0 => 0,
+                    1 if self.rule.survival[index] => 1,
+                    // Alive or dying cells move one refractory state further, dying at the end
+                    state => (state + 1) % self.rule.states,
+                };
+            }
+        }
+
+        self.cells = next;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn normalised(rule: &str) -> String {
+        GenerationsRule::parse(rule).unwrap().to_string()
+    }
+
+    #[test]
+    fn notations_normalise_to_bsc() {
+        assert_eq!(normalised("345/2/4"), "B2/S345/C4");
+        assert_eq!(normalised("23/3"), "B3/S23");
+        assert_eq!(normalised("B2/S/C3"), "B2/S/C3");
+        assert_eq!(normalised("s23/b3/g5"), "B3/S23/C5");
+        assert!(GenerationsRule::parse("B3").is_err());
+        assert!(GenerationsRule::parse("B3/S23/C4/C5").is_err());
+    }
+
+    #[test]
+    fn short_neighbourhood_suffixes() {
+        let hexagonal = GenerationsRule::parse("B2/S34H").unwrap();
+        assert!(hexagonal.neighborhood == Neighborhood::Hexagonal(1));
+        assert!(hexagonal.birth[2] && !hexagonal.birth[3]);
+        assert!(hexagonal.survival[3] && hexagonal.survival[4] && !hexagonal.survival[2]);
+        assert_eq!(hexagonal.to_string(), "B2/S34H");
+
+        let von_neumann = GenerationsRule::parse("B1/S/C3V").unwrap();
+        assert!(von_neumann.neighborhood == Neighborhood::VonNeumann(1));
+        assert_eq!(von_neumann.states, 3);
+        assert_eq!(von_neumann.to_string(), "B1/S/C3V");
+
+        // Only the range 1 neighbourhoods take conditions up to their size
+        assert!(GenerationsRule::parse("B5/S23V").is_err());
+    }
+
+    #[test]
+    fn display_round_trips() {
+        for rule in [
+            "B3/S23",
+            "B2/S/C3",
+            "B2/S345/C4",
+            "B2-a/S12",
+            "B2/S34H",
+            "B1/S/C3V",
+            "R5,C2,M1,S34-58,B34-45,NM",
+            "R2,C3,M0,S2-4,B3,NN",
+        ] {
+            let parsed = GenerationsRule::parse(rule).unwrap();
+            let again = GenerationsRule::parse(&parsed.to_string()).unwrap();
+            assert!(parsed == again, "{} came back as {}", rule, again);
+        }
+    }
+
+    #[test]
+    fn brians_brain_cells_decay() {
+        let mut grid = vec![vec![false; 6]; 6];
+        grid[2][2] = true;
+        let mut board = GenerationsBoard::from_grid(&grid, GenerationsRule::parse("B2/S/C3").unwrap());
+        // A lone cell can't survive or give birth, so it fades through state 2
+        board.step();
+        assert_eq!(board.cells()[2][2], 2);
+        board.step();
+        assert_eq!(board.cells()[2][2], 0);
+        assert!(board.cells().iter().flatten().all(|&state| state == 0));
+
+        // Dying cells don't count: two neighbours in state 2 cause no birth
+        board.set_cell(1, 1, 2);
+        board.set_cell(3, 1, 2);
+        board.step();
+        assert_eq!(board.cells()[0][2], 0);
+        assert!(board.cells().iter().flatten().all(|&state| state == 0));
+    }
+}
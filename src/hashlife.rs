@@ -0,0 +1,501 @@
+use std::collections::HashMap;
+use crate::macrocell::{Macrocell, Node as MacrocellNode};
+
+// Leaf node ids: a level 0 node is a single cell, dead or alive
+const DEAD: u32 = 0;
+const ALIVE: u32 = 1;
+
+// Smallest root level we keep around (8x8 cells)
+const MIN_LEVEL: u8 = 3;
+
+// A canonical quadtree node. A node of level n covers 2^n x 2^n cells
+#[derive(Clone, Copy)]
+struct Node {
+    nw: u32,
+    ne: u32,
+    sw: u32,
+    se: u32,
+    level: u8,
+    population: u64,
+}
+
+// Quadtree-memoized Game of Life engine (Gosper's HashLife).
+// Unlike the dense grid it runs on an unbounded plane, so nothing wraps around:
+// the universe grows as the pattern does and is centred on the origin.
+pub struct HashLife {
+    nodes: Vec<Node>,
+    index: HashMap<(u32, u32, u32, u32), u32>,
+    results: HashMap<(u32, u8), u32>,
+    empty: Vec<u32>,
+    root: u32,
+    max_nodes: usize,
+}
+
+impl HashLife {
+    pub fn new(max_nodes: usize) -> Self {
+        let leaf = |population| Node { nw: DEAD, ne: DEAD, sw: DEAD, se: DEAD, level: 0, population };
+        let mut universe = HashLife {
+            nodes: vec![leaf(0), leaf(1)],
+            index: HashMap::new(),
+            results: HashMap::new(),
+            empty: Vec::new(),
+            root: DEAD,
+            max_nodes,
+        };
+        universe.root = universe.empty(MIN_LEVEL);
+        universe
+    }
+
+    // Build a universe from the dense grid, with grid cell (0, 0) at the origin
+    pub fn from_grid(grid: &[Vec<bool>], max_nodes: usize) -> Self {
+        let mut universe = HashLife::new(max_nodes);
+        let height = grid.len() as i64;
+        let width = grid.first().map_or(0, |row| row.len()) as i64;
+
+        // Pick a root big enough that the grid fits in its bottom-right quadrant
+        let mut level = MIN_LEVEL;
+        while (1i64 << (level - 1)) < width.max(height) {
+            level += 1;
+        }
+        let half = 1i64 << (level - 1);
+        universe.root = universe.build_from_grid(grid, level, -half, -half);
+        universe
+    }
+
+    // Build a universe from a two-state macrocell file, node for node; the
+    // file's root is centred on the origin as ours is
+    pub fn from_macrocell(macrocell: &Macrocell, max_nodes: usize) -> Result<Self, String> {
+        let mut universe = HashLife::new(max_nodes);
+        let mut ids = Vec::with_capacity(macrocell.nodes.len());
+        for node in &macrocell.nodes {
+            let id = match *node {
+                MacrocellNode::Cells(rows) => universe.leaf_block(&rows, 3, 0, 0),
+                MacrocellNode::Branch(level, children) => {
+                    let mut quadrants = [DEAD; 4];
+                    for (quadrant, child) in quadrants.iter_mut().zip(children) {
+                        *quadrant = if child == 0 { universe.empty(level - 1) } else { ids[child - 1] };
+                    }
+                    universe.join(quadrants[0], quadrants[1], quadrants[2], quadrants[3])
+                },
+                MacrocellNode::States(_) => return Err("HashLife only runs two-state patterns".to_string()),
+            };
+            ids.push(id);
+        }
+        if let Some(&root) = ids.last() {
+            universe.root = root;
+        }
+        while universe.level() < MIN_LEVEL {
+            universe.expand();
+        }
+        Ok(universe)
+    }
+
+    // The whole universe as a macrocell file, each distinct node written once
+    pub fn to_macrocell(&self) -> Macrocell {
+        let mut macrocell = Macrocell::default();
+        let mut written = HashMap::new();
+        let mut index = HashMap::new();
+        self.write_macrocell_node(self.root, &mut macrocell, &mut written, &mut index);
+        macrocell
+    }
+
+    // Dense view of the universe through a camera whose top-left cell is (left, top)
+    pub fn window(&self, left: i64, top: i64, width: usize, height: usize) -> Vec<Vec<bool>> {
+        let mut grid = vec![vec![false; width]; height];
+        let level = self.nodes[self.root as usize].level;
+        let half = 1i64 << (level - 1);
+        self.write_to_grid(self.root, -half - left, -half - top, &mut grid);
+        grid
+    }
+
+    // Advance the universe by 2^k generations
+    pub fn step(&mut self, k: u8) {
+        if self.nodes.len() > self.max_nodes {
+            self.collect_garbage();
+        }
+
+        // Pad the root until the pattern sits in its central quarter and the
+        // root is big enough that 2^k generations can't push cells past its centre
+        while self.level() < k + 3 || !self.is_padded() {
+            self.expand();
+        }
+        self.root = self.successor(self.root, k);
+    }
+
+    // Set a single cell, growing the root until it covers the cell
+    pub fn set_cell(&mut self, x: i64, y: i64, alive: bool) {
+        loop {
+            let half = 1i64 << (self.level() - 1);
+            if (-half..half).contains(&x) && (-half..half).contains(&y) {
+                break;
+            }
+            self.expand();
+        }
+        let level = self.level();
+        let half = 1i64 << (level - 1);
+        self.root = self.set_in(self.root, level, x + half, y + half, alive);
+    }
+
+    pub fn population(&self) -> u64 {
+        self.nodes[self.root as usize].population
+    }
+
+    fn level(&self) -> u8 {
+        self.nodes[self.root as usize].level
+    }
+
+    // Find or create the canonical node with the given children
+    fn join(&mut self, nw: u32, ne: u32, sw: u32, se: u32) -> u32 {
+        if let Some(&id) = self.index.get(&(nw, ne, sw, se)) {
+            return id;
+        }
+        // Saturating, since a deep macrocell file can hold more than 2^64 cells
+        let population = [nw, ne, sw, se]
+            .iter()
+            .fold(0u64, |total, &child| total.saturating_add(self.nodes[child as usize].population));
+        let node = Node { nw, ne, sw, se, level: self.nodes[nw as usize].level + 1, population };
+        let id = self.nodes.len() as u32;
+        self.nodes.push(node);
+        self.index.insert((nw, ne, sw, se), id);
+        id
+    }
+
+    // Empty node of the given level (memoized per level)
+    fn empty(&mut self, level: u8) -> u32 {
+        while self.empty.len() <= level as usize {
+            let id = match self.empty.last() {
+                Some(&below) => self.join(below, below, below, below),
+                None => DEAD,
+            };
+            self.empty.push(id);
+        }
+        self.empty[level as usize]
+    }
+
+    // Wrap the root in a border of empty space, doubling its size around the same centre
+    fn expand(&mut self) {
+        let root = self.nodes[self.root as usize];
+        let e = self.empty(root.level - 1);
+        let nw = self.join(e, e, e, root.nw);
+        let ne = self.join(e, e, root.ne, e);
+        let sw = self.join(e, root.sw, e, e);
+        let se = self.join(root.se, e, e, e);
+        self.root = self.join(nw, ne, sw, se);
+    }
+
+    // Level n-1 node at the centre of a level n node
+    fn centre(&mut self, id: u32) -> u32 {
+        let node = self.nodes[id as usize];
+        let (nw, ne, sw, se) = (
+            self.nodes[node.nw as usize],
+            self.nodes[node.ne as usize],
+            self.nodes[node.sw as usize],
+            self.nodes[node.se as usize],
+        );
+        self.join(nw.se, ne.sw, sw.ne, se.nw)
+    }
+
+    fn is_padded(&mut self) -> bool {
+        let half = self.centre(self.root);
+        let quarter = self.centre(half);
+        self.nodes[quarter as usize].population == self.population()
+    }
+
+    // State of cell (x, y) inside a node, with (0, 0) at its top-left corner
+    fn cell(&self, id: u32, x: u32, y: u32) -> bool {
+        let node = self.nodes[id as usize];
+        if node.level == 0 {
+            return id == ALIVE;
+        }
+        let half = 1 << (node.level - 1);
+        match (x < half, y < half) {
+            (true, true) => self.cell(node.nw, x, y),
+            (false, true) => self.cell(node.ne, x - half, y),
+            (true, false) => self.cell(node.sw, x, y - half),
+            (false, false) => self.cell(node.se, x - half, y - half),
+        }
+    }
+
+    // Brute-force one generation of a 4x4 node, returning its 2x2 centre
+    fn base_step(&mut self, id: u32) -> u32 {
+        let mut next = [DEAD; 4];
+        for (i, (cx, cy)) in [(1, 1), (2, 1), (1, 2), (2, 2)].into_iter().enumerate() {
+            let mut live_neighbors = 0;
+            for dy in 0..3 {
+                for dx in 0..3 {
+                    if (dx != 1 || dy != 1) && self.cell(id, cx + dx - 1, cy + dy - 1) {
+                        live_neighbors += 1;
+                    }
+                }
+            }
+            let alive = self.cell(id, cx, cy);
+            if live_neighbors == 3 || (alive && live_neighbors == 2) {
+                next[i] = ALIVE;
+            }
+        }
+        self.join(next[0], next[1], next[2], next[3])
+    }
+
+    // Centre of a level n node advanced by 2^j generations (j <= n - 2)
+    fn successor(&mut self, id: u32, j: u8) -> u32 {
+        if let Some(&result) = self.results.get(&(id, j)) {
+            return result;
+        }
+
+        let node = self.nodes[id as usize];
+        let result = if node.population == 0 {
+            self.empty(node.level - 1)
+        } else if node.level == 2 {
+            self.base_step(id)
+        } else {
+            let (nw, ne, sw, se) = (
+                self.nodes[node.nw as usize],
+                self.nodes[node.ne as usize],
+                self.nodes[node.sw as usize],
+                self.nodes[node.se as usize],
+            );
+
+            // The nine overlapping level n-1 squares covering the node
+            let sub = [
+                node.nw,
+                self.join(nw.ne, ne.nw, nw.se, ne.sw),
+                node.ne,
+                self.join(nw.sw, nw.se, sw.nw, sw.ne),
+                self.join(nw.se, ne.sw, sw.ne, se.nw),
+                self.join(ne.sw, ne.se, se.nw, se.ne),
+                node.sw,
+                self.join(sw.ne, se.nw, sw.se, se.sw),
+                node.se,
+            ];
+
+            // At full speed both halves advance 2^(n-3) generations; for smaller
+            // steps the first half just takes the centres without advancing
+            let full_speed = j + 2 == node.level;
+            let mut c = [DEAD; 9];
+            for (slot, &square) in c.iter_mut().zip(sub.iter()) {
+                *slot = if full_speed { self.successor(square, j - 1) } else { self.centre(square) };
+            }
+
+            let quads = [
+                self.join(c[0], c[1], c[3], c[4]),
+                self.join(c[1], c[2], c[4], c[5]),
+                self.join(c[3], c[4], c[6], c[7]),
+                self.join(c[4], c[5], c[7], c[8]),
+            ];
+            let inner_j = if full_speed { j - 1 } else { j };
+            let mut r = [DEAD; 4];
+            for (slot, &quad) in r.iter_mut().zip(quads.iter()) {
+                *slot = self.successor(quad, inner_j);
+            }
+            self.join(r[0], r[1], r[2], r[3])
+        };
+
+        self.results.insert((id, j), result);
+        result
+    }
+
+    fn build_from_grid(&mut self, grid: &[Vec<bool>], level: u8, x0: i64, y0: i64) -> u32 {
+        let height = grid.len() as i64;
+        let width = grid.first().map_or(0, |row| row.len()) as i64;
+        let size = 1i64 << level;
+        if x0 >= width || y0 >= height || x0 + size <= 0 || y0 + size <= 0 {
+            return self.empty(level);
+        }
+        if level == 0 {
+            return if grid[y0 as usize][x0 as usize] { ALIVE } else { DEAD };
+        }
+        let half = size / 2;
+        let nw = self.build_from_grid(grid, level - 1, x0, y0);
+        let ne = self.build_from_grid(grid, level - 1, x0 + half, y0);
+        let sw = self.build_from_grid(grid, level - 1, x0, y0 + half);
+        let se = self.build_from_grid(grid, level - 1, x0 + half, y0 + half);
+        self.join(nw, ne, sw, se)
+    }
+
+    // Rebuild the path from a node down to cell (x, y), relative to its top-left corner
+    fn set_in(&mut self, id: u32, level: u8, x: i64, y: i64, alive: bool) -> u32 {
+        if level == 0 {
+            return if alive { ALIVE } else { DEAD };
+        }
+        let node = self.nodes[id as usize];
+        let half = 1i64 << (level - 1);
+        let (mut nw, mut ne, mut sw, mut se) = (node.nw, node.ne, node.sw, node.se);
+        match (x < half, y < half) {
+            (true, true) => nw = self.set_in(nw, level - 1, x, y, alive),
+            (false, true) => ne = self.set_in(ne, level - 1, x - half, y, alive),
+            (true, false) => sw = self.set_in(sw, level - 1, x, y - half, alive),
+            (false, false) => se = self.set_in(se, level - 1, x - half, y - half, alive),
+        }
+        self.join(nw, ne, sw, se)
+    }
+
+    fn write_to_grid(&self, id: u32, x0: i64, y0: i64, grid: &mut [Vec<bool>]) {
+        let node = self.nodes[id as usize];
+        let height = grid.len() as i64;
+        let width = grid.first().map_or(0, |row| row.len()) as i64;
+        let size = 1i64 << node.level;
+        if node.population == 0 || x0 >= width || y0 >= height || x0 + size <= 0 || y0 + size <= 0 {
+            return;
+        }
+        if node.level == 0 {
+            grid[y0 as usize][x0 as usize] = true;
+            return;
+        }
+        let half = size / 2;
+        self.write_to_grid(node.nw, x0, y0, grid);
+        self.write_to_grid(node.ne, x0 + half, y0, grid);
+        self.write_to_grid(node.sw, x0, y0 + half, grid);
+        self.write_to_grid(node.se, x0 + half, y0 + half, grid);
+    }
+
+    // Node for the square of a level 3 macrocell leaf at (x0, y0)
+    fn leaf_block(&mut self, rows: &[u8; 8], level: u8, x0: usize, y0: usize) -> u32 {
+        if level == 0 {
+            return if rows[y0] & (1 << x0) != 0 { ALIVE } else { DEAD };
+        }
+        let half = 1 << (level - 1);
+        let nw = self.leaf_block(rows, level - 1, x0, y0);
+        let ne = self.leaf_block(rows, level - 1, x0 + half, y0);
+        let sw = self.leaf_block(rows, level - 1, x0, y0 + half);
+        let se = self.leaf_block(rows, level - 1, x0 + half, y0 + half);
+        self.join(nw, ne, sw, se)
+    }
+
+    // Write a node's children before it, returning its position in the file (0 when empty)
+    fn write_macrocell_node(&self, id: u32, macrocell: &mut Macrocell, written: &mut HashMap<u32, usize>, index: &mut HashMap<MacrocellNode, usize>) -> usize {
+        let node = self.nodes[id as usize];
+        if node.population == 0 {
+            return 0;
+        }
+        if let Some(&position) = written.get(&id) {
+            return position;
+        }
+        let macrocell_node = if node.level == 3 {
+            let mut rows = [0u8; 8];
+            for (y, row) in rows.iter_mut().enumerate() {
+                *row = (0..8).filter(|&x| self.cell(id, x, y as u32)).fold(0, |bits, x| bits | 1 << x);
+            }
+            MacrocellNode::Cells(rows)
+        } else {
+            let mut children = [0; 4];
+            for (child, quadrant) in children.iter_mut().zip([node.nw, node.ne, node.sw, node.se]) {
+                *child = self.write_macrocell_node(quadrant, macrocell, written, index);
+            }
+            MacrocellNode::Branch(node.level, children)
+        };
+        let position = macrocell.push(macrocell_node, index);
+        written.insert(id, position);
+        position
+    }
+
+    // Drop every node not reachable from the root and forget memoized results
+    fn collect_garbage(&mut self) {
+        let old = std::mem::take(&mut self.nodes);
+        self.index.clear();
+        self.results.clear();
+        self.empty.clear();
+        self.nodes.push(old[DEAD as usize]);
+        self.nodes.push(old[ALIVE as usize]);
+
+        let mut remap = vec![u32::MAX; old.len()];
+        remap[DEAD as usize] = DEAD;
+        remap[ALIVE as usize] = ALIVE;
+        self.root = self.copy_node(&old, &mut remap, self.root);
+    }
+
+    fn copy_node(&mut self, old: &[Node], remap: &mut [u32], id: u32) -> u32 {
+        if remap[id as usize] != u32::MAX {
+            return remap[id as usize];
+        }
+        let node = old[id as usize];
+        let nw = self.copy_node(old, remap, node.nw);
+        let ne = self.copy_node(old, remap, node.ne);
+        let sw = self.copy_node(old, remap, node.sw);
+        let se = self.copy_node(old, remap, node.se);
+        let new_id = self.join(nw, ne, sw, se);
+        remap[id as usize] = new_id;
+        new_id
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pattern::Pattern;
+    use crate::sparse::SparseBoard;
+
+    #[test]
+    fn macrocell_round_trip_keeps_the_pattern() {
+        // An R-pentomino and a block far enough apart to need several levels
+        let mut cells = vec![vec![0; 40]; 30];
+        for (x, y) in [(1, 0), (2, 0), (0, 1), (1, 1), (1, 2), (38, 28), (39, 28), (38, 29), (39, 29)] {
+            cells[y][x] = 1;
+        }
+        let pattern = Pattern { rule: Some("B3/S23".to_string()), cells, ..Pattern::default() };
+        let macrocell = Macrocell::parse(&Macrocell::from_pattern(&pattern).write()).unwrap();
+
+        let universe = HashLife::from_macrocell(&macrocell, 1 << 16).unwrap();
+        assert_eq!(universe.population(), 9);
+        let written = universe.to_macrocell();
+        assert_eq!(HashLife::from_macrocell(&written, 1 << 16).unwrap().population(), 9);
+        assert_eq!(written.to_pattern().unwrap().cells, pattern.cells);
+    }
+
+    const R_PENTOMINO: [(i64, i64); 5] = [(1, 0), (2, 0), (0, 1), (1, 1), (1, 2)];
+
+    fn r_pentomino(max_nodes: usize) -> (HashLife, SparseBoard) {
+        let (mut universe, mut board) = (HashLife::new(max_nodes), SparseBoard::new());
+        for (x, y) in R_PENTOMINO {
+            universe.set_cell(x, y, true);
+            board.set(x, y, true);
+        }
+        (universe, board)
+    }
+
+    // Both boards around the origin, far enough out for the R-pentomino's first gliders
+    fn same_view(universe: &HashLife, board: &SparseBoard) {
+        assert_eq!(universe.window(-100, -100, 200, 200), board.window(-100, -100, 200, 200));
+        assert_eq!(universe.population(), board.population());
+    }
+
+    #[test]
+    fn single_steps_match_the_sparse_board() {
+        let (mut universe, mut board) = r_pentomino(1 << 20);
+        for _ in 0..150 {
+            universe.step(0);
+            board.step();
+            same_view(&universe, &board);
+        }
+    }
+
+    #[test]
+    fn jumps_match_the_sparse_board() {
+        let (mut universe, mut board) = r_pentomino(1 << 20);
+        for k in [1, 3, 5, 2, 4] {
+            universe.step(k);
+            for _ in 0..1 << k {
+                board.step();
+            }
+            same_view(&universe, &board);
+        }
+    }
+
+    #[test]
+    fn garbage_collection_keeps_the_pattern() {
+        // So few nodes that every step starts with a collection
+        let (mut universe, mut board) = r_pentomino(64);
+        for k in [0, 0, 2, 0, 3, 1, 4] {
+            universe.step(k);
+            for _ in 0..1 << k {
+                board.step();
+            }
+            same_view(&universe, &board);
+        }
+    }
+
+    #[test]
+    fn multi_state_macrocells_are_refused() {
+        let pattern = Pattern { cells: vec![vec![1, 2]], ..Pattern::default() };
+        assert!(HashLife::from_macrocell(&Macrocell::from_pattern(&pattern), 1 << 16).is_err());
+    }
+}
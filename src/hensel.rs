@@ -0,0 +1,245 @@
+use std::sync::OnceLock;
+
+// Neighbour offsets in the order of the bits of a neighbourhood mask:
+// bit 7 is the top-left neighbour and bit 0 the bottom-right one, in reading order
+//   7 6 5
+//   4 . 3
+//   2 1 0
+pub const NEIGHBOR_OFFSETS: [(i32, i32); 8] = [
+    (-1, -1), (0, -1), (1, -1),
+    (-1, 0), (1, 0),
+    (-1, 1), (0, 1), (1, 1),
+];
+
+// Hensel's letters for each neighbour count, in canonical order
+const LETTERS: [&str; 9] = [
+    "", "ce", "ceaikn", "ceaiknjqry", "ceaiknjqrytwz", "ceaiknjqry", "ceaikn", "ce", "",
+];
+
+// One representative configuration per letter for counts 1 to 4; counts 5 to 7
+// are the complements of counts 3 to 1 with the same letter
+const REPRESENTATIVES: [&[u8]; 5] = [
+    &[],
+    &[0b0000_0001, 0b0000_0010],
+    &[0b0000_0101, 0b0000_1010, 0b0000_0011, 0b0001_1000, 0b0001_0001, 0b0010_0100],
+    &[
+        0b0010_0101, 0b0001_1010, 0b0000_1011, 0b0000_0111, 0b0011_0010,
+        0b0000_1101, 0b0000_1110, 0b0010_0110, 0b0001_1001, 0b0011_0001,
+    ],
+    &[
+        0b1010_0101, 0b0101_1010, 0b0000_1111, 0b0001_1101, 0b0011_0011,
+        0b0010_0111, 0b0011_1010, 0b0011_0110, 0b0001_1011, 0b0011_0101,
+        0b0011_1001, 0b0010_1110, 0b0011_1100,
+    ],
+];
+
+// Neighbour count and index of the Hensel letter for a neighbourhood mask
+pub fn classify(mask: u8) -> (u8, u8) {
+    static TABLE: OnceLock<[(u8, u8); 256]> = OnceLock::new();
+    TABLE.get_or_init(build_table)[mask as usize]
+}
+
+// Build the 256-entry lookup table by spreading each representative over the
+// 8 symmetries of the square
+fn build_table() -> [(u8, u8); 256] {
+    let mut table = [(0, 0); 256];
+    for mask in 0..=255u8 {
+        table[mask as usize].0 = mask.count_ones() as u8;
+    }
+    for (count, representatives) in REPRESENTATIVES.iter().enumerate() {
+        for (letter, &representative) in representatives.iter().enumerate() {
+            for symmetry in 0..8 {
+                let mask = transform(representative, symmetry);
+                table[mask as usize] = (count as u8, letter as u8);
+                if count < 4 {
+                    table[!mask as usize] = (8 - count as u8, letter as u8);
+                }
+            }
+        }
+    }
+    table
+}
+
+// Apply one of the 8 rotations/reflections of the square to a mask
+fn transform(mask: u8, symmetry: u8) -> u8 {
+    let mut result = 0;
+    for (bit, &(dx, dy)) in NEIGHBOR_OFFSETS.iter().enumerate() {
+        if mask & (0x80 >> bit) == 0 {
+            continue;
+        }
+        let (mut x, mut y) = (dx, dy);
+        for _ in 0..symmetry % 4 {
+            (x, y) = (-y, x);
+        }
+        if symmetry >= 4 {
+            x = -x;
+        }
+        let target = NEIGHBOR_OFFSETS.iter().position(|&offset| offset == (x, y)).unwrap();
+        result |= 0x80 >> target;
+    }
+    result
+}
+
+// Parse the conditions of one half of a rule, such as "2-a" or "12" or "3aq4",
+// into the set of neighbourhood masks it accepts. A count alone takes every
+// letter, "n-xy" takes all but the listed letters and "nxy" only those
+pub fn parse_conditions(spec: &str) -> Result<[bool; 256], String> {
+    let mut accepted = [false; 256];
+    let mut chars = spec.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        let count = match c.to_digit(10) {
+            Some(n) if n <= 8 => n as usize,
+            _ => return Err(format!("'{}' is not a neighbour count between 0 and 8", c)),
+        };
+        let negated = chars.next_if_eq(&'-').is_some();
+        let mut letters = String::new();
+        while let Some(letter) = chars.next_if(|c| c.is_ascii_lowercase()) {
+            if !LETTERS[count].contains(letter) {
+                return Err(format!("'{}' is not a Hensel letter for {} neighbours", letter, count));
+            }
+            letters.push(letter);
+        }
+        if negated && letters.is_empty() {
+            return Err(format!("'{}-' must be followed by letters", count));
+        }
+
+        for mask in 0..=255u8 {
+            let (mask_count, letter) = classify(mask);
+            if mask_count as usize != count {
+                continue;
+            }
+            let listed = LETTERS[count]
+                .chars()
+                .nth(letter as usize)
+                .is_some_and(|l| letters.contains(l));
+            if letters.is_empty() || listed != negated {
+                accepted[mask as usize] = true;
+            }
+        }
+    }
+
+    Ok(accepted)
+}
+
+// Write an accepted set back in canonical form: counts in order, using the
+// shorter of the listed and the negated letters, so parsing round-trips
+pub fn format_conditions(accepted: &[bool; 256]) -> String {
+    let mut spec = String::new();
+    for (count, letters) in LETTERS.iter().enumerate() {
+        let mut present = vec![false; letters.len().max(1)];
+        for mask in 0..=255u8 {
+            let (mask_count, letter) = classify(mask);
+            if mask_count as usize == count && accepted[mask as usize] {
+                present[letter as usize] = true;
+            }
+        }
+
+        let total = present.len();
+        let included = present.iter().filter(|&&p| p).count();
+        if included == 0 {
+            continue;
+        }
+        spec.push(char::from(b'0' + count as u8));
+        if included == total {
+            continue;
+        }
+        let negated = included * 2 > total;
+        if negated {
+            spec.push('-');
+        }
+        for (letter, &p) in letters.chars().zip(present.iter()) {
+            if p != negated {
+                spec.push(letter);
+            }
+        }
+    }
+    spec
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // One neighbourhood per letter, drawn as in Hensel's and LifeWiki's tables
+    const DRAWN: [(&str, [&str; 3]); 31] = [
+        ("1c", ["X..", ".o.", "..."]),
+        ("1e", [".X.", ".o.", "..."]),
+        ("2c", ["X.X", ".o.", "..."]),
+        ("2e", [".X.", "Xo.", "..."]),
+        ("2a", ["XX.", ".o.", "..."]),
+        ("2i", ["...", "XoX", "..."]),
+        ("2k", ["X..", ".oX", "..."]),
+        ("2n", ["..X", ".o.", "X.."]),
+        ("3c", ["X.X", ".o.", "X.."]),
+        ("3e", [".X.", "XoX", "..."]),
+        ("3a", ["XX.", "Xo.", "..."]),
+        ("3i", ["XXX", ".o.", "..."]),
+        ("3k", [".X.", ".oX", "X.."]),
+        ("3n", ["X.X", "Xo.", "..."]),
+        ("3j", [".XX", "Xo.", "..."]),
+        ("3q", [".XX", ".o.", "X.."]),
+        ("3r", ["X..", "XoX", "..."]),
+        ("3y", ["X..", ".oX", "X.."]),
+        ("4c", ["X.X", ".o.", "X.X"]),
+        ("4e", [".X.", "XoX", ".X."]),
+        ("4a", ["XXX", "Xo.", "..."]),
+        ("4i", ["X.X", "XoX", "..."]),
+        ("4k", ["XX.", ".oX", "X.."]),
+        ("4n", ["XXX", ".o.", "X.."]),
+        ("4j", [".X.", "XoX", "X.."]),
+        ("4q", [".XX", ".oX", "X.."]),
+        ("4r", ["XX.", "XoX", "..."]),
+        ("4y", ["X.X", ".oX", "X.."]),
+        ("4t", ["X..", "XoX", "X.."]),
+        ("4w", [".XX", "Xo.", "X.."]),
+        ("4z", ["..X", "XoX", "X.."]),
+    ];
+
+    fn mask(rows: [&str; 3]) -> u8 {
+        let mut mask = 0;
+        for (bit, &(dx, dy)) in NEIGHBOR_OFFSETS.iter().enumerate() {
+            if rows[(dy + 1) as usize].as_bytes()[(dx + 1) as usize] == b'X' {
+                mask |= 0x80 >> bit;
+            }
+        }
+        mask
+    }
+
+    fn letter(count: u8, index: u8) -> char {
+        LETTERS[count as usize].chars().nth(index as usize).unwrap()
+    }
+
+    #[test]
+    fn drawn_neighbourhoods_get_their_letters() {
+        for (name, rows) in DRAWN {
+            let count = name.as_bytes()[0] - b'0';
+            let expected = name.chars().nth(1).unwrap();
+            for symmetry in 0..8 {
+                let (n, index) = classify(transform(mask(rows), symmetry));
+                assert_eq!((n, letter(n, index)), (count, expected), "{} under symmetry {}", name, symmetry);
+                // Counts 5 to 7 are the complements of 3 to 1, with the same letters
+                if count < 4 {
+                    let (n, index) = classify(!transform(mask(rows), symmetry));
+                    assert_eq!((n, letter(n, index)), (8 - count, expected), "complement of {}", name);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn conditions_round_trip() {
+        for spec in ["", "3", "23", "2-a3", "1e2k3-cy4twz5r6ci78", "3-jqr4-tw", "0124-t"] {
+            let accepted = parse_conditions(spec).unwrap();
+            assert_eq!(format_conditions(&accepted), spec);
+            assert_eq!(parse_conditions(&format_conditions(&accepted)).unwrap(), accepted);
+        }
+    }
+
+    #[test]
+    fn t_and_w_differ() {
+        let t = parse_conditions("4t").unwrap();
+        assert!(t[mask(["X..", "XoX", "X.."]) as usize]);
+        assert!(!t[mask([".XX", "Xo.", "X.."]) as usize]);
+    }
+}
@@ -0,0 +1,109 @@
+use std::collections::VecDeque;
+
+// One recorded board, run-length encoded in reading order
+struct Frame<T> {
+    generation: u64,
+    width: usize,
+    runs: Vec<(u32, T)>,
+}
+
+// Bounded record of past boards, oldest first, with a cursor on the one being
+// shown. Boards are run-length encoded, which keeps the mostly empty boards of
+// Life down to a few hundred runs each
+pub struct History<T> {
+    frames: VecDeque<Frame<T>>,
+    capacity: usize,
+    position: usize,
+}
+
+impl<T: Copy + PartialEq> History<T> {
+    pub fn new(capacity: usize) -> Self {
+        History { frames: VecDeque::new(), capacity: capacity.max(1), position: 0 }
+    }
+
+    pub fn clear(&mut self) {
+        self.frames.clear();
+        self.position = 0;
+    }
+
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    // Record a board as the newest generation. When an older generation is being
+    // shown, the generations after it are dropped and the record branches from there
+    pub fn push(&mut self, generation: u64, cells: &[Vec<T>]) {
+        self.frames.truncate(self.position + 1);
+        let mut runs: Vec<(u32, T)> = Vec::new();
+        for &cell in cells.iter().flatten() {
+            match runs.last_mut() {
+                Some((length, value)) if *value == cell => *length += 1,
+                _ => runs.push((1, cell)),
+            }
+        }
+        let width = cells.first().map_or(0, |row| row.len());
+        self.frames.push_back(Frame { generation, width, runs });
+        while self.frames.len() > self.capacity {
+            self.frames.pop_front();
+        }
+        self.position = self.frames.len() - 1;
+    }
+
+    // Move the cursor to a recorded generation (clamped to the record) and
+    // return its number and board
+    pub fn seek(&mut self, position: usize) -> Option<(u64, Vec<Vec<T>>)> {
+        self.position = position.min(self.frames.len().checked_sub(1)?);
+        let frame = &self.frames[self.position];
+        let cells: Vec<T> = frame.runs.iter().flat_map(|&(length, value)| std::iter::repeat_n(value, length as usize)).collect();
+        Some((frame.generation, cells.chunks(frame.width.max(1)).map(|row| row.to_vec()).collect()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A 3x2 board holding the generation number in one cell
+    fn board(generation: u64) -> Vec<Vec<u64>> {
+        vec![vec![0, generation, 0], vec![7, 7, 7]]
+    }
+
+    #[test]
+    fn oldest_generations_are_evicted() {
+        let mut history = History::new(3);
+        for generation in 0..5 {
+            history.push(generation, &board(generation));
+        }
+        assert_eq!(history.len(), 3);
+        assert_eq!(history.position(), 2);
+        assert_eq!(history.seek(0), Some((2, board(2))));
+        assert_eq!(history.seek(2), Some((4, board(4))));
+        // Seeking past the end stops at the newest generation
+        assert_eq!(history.seek(10), Some((4, board(4))));
+        assert_eq!(history.position(), 2);
+
+        history.clear();
+        assert_eq!(history.seek(0), None);
+    }
+
+    #[test]
+    fn pushing_after_a_seek_branches() {
+        let mut history = History::new(10);
+        for generation in 0..6 {
+            history.push(generation, &board(generation));
+        }
+        assert_eq!(history.seek(2), Some((2, board(2))));
+
+        // Generations 3 to 5 of the old branch are dropped
+        history.push(3, &board(30));
+        assert_eq!(history.len(), 4);
+        assert_eq!(history.position(), 3);
+        assert_eq!(history.seek(3), Some((3, board(30))));
+        assert_eq!(history.seek(5), Some((3, board(30))));
+        assert_eq!(history.seek(1), Some((1, board(1))));
+    }
+}
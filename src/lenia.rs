@@ -0,0 +1,255 @@
+use std::fmt;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use crate::fft::{Complex, Fft2d};
+
+// Parameters of a Lenia world: kernel radius R in cells, the heights of the
+// kernel's concentric rings (b), the growth function's centre (mu) and width
+// (sigma), and the time step dt
+#[derive(Clone, PartialEq)]
+pub struct LeniaParams {
+    pub radius: f32,
+    pub peaks: Vec<f32>,
+    pub mu: f32,
+    pub sigma: f32,
+    pub dt: f32,
+}
+
+impl LeniaParams {
+    // Named worlds: "orbium" (single ring, home of the Orbium glider) and
+    // "geminium" (three rings, home of the self-replicating Hydrogeminium)
+    pub fn preset(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "orbium" => Some(LeniaParams { radius: 13.0, peaks: vec![1.0], mu: 0.15, sigma: 0.015, dt: 0.1 }),
+            "geminium" => Some(LeniaParams { radius: 18.0, peaks: vec![0.5, 1.0, 0.667], mu: 0.26, sigma: 0.036, dt: 0.1 }),
+            _ => None,
+        }
+    }
+
+    // Parse a preset name or a list like "R=13,mu=0.15,sigma=0.015,dt=0.1,b=1:0.5",
+    // where missing fields keep the orbium values
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let spec = spec.trim();
+        if let Some(params) = LeniaParams::preset(spec) {
+            return Ok(params);
+        }
+
+        let mut params = LeniaParams::preset("orbium").unwrap();
+        for field in spec.split(',') {
+            let (key, value) = field.split_once('=').ok_or(format!("'{}' should look like key=value", field))?;
+            let number = |text: &str| text.trim().parse::<f32>().map_err(|_| format!("'{}' is not a number", text));
+            match key.trim() {
+                "R" => params.radius = number(value)?,
+                "mu" => params.mu = number(value)?,
+                "sigma" => params.sigma = number(value)?,
+                "dt" => params.dt = number(value)?,
+                "b" => params.peaks = value.split(':').map(number).collect::<Result<_, _>>()?,
+                _ => return Err(format!("unknown Lenia parameter '{}'", key)),
+            }
+        }
+
+        if !(1.0..=64.0).contains(&params.radius) {
+            return Err(format!("radius {} should be between 1 and 64", params.radius));
+        }
+        if params.peaks.is_empty() || params.sigma <= 0.0 || params.dt <= 0.0 || params.dt > 1.0 {
+            return Err("Lenia needs at least one ring, sigma > 0 and 0 < dt <= 1".to_string());
+        }
+        Ok(params)
+    }
+}
+
+impl fmt::Display for LeniaParams {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let peaks: Vec<String> = self.peaks.iter().map(|peak| peak.to_string()).collect();
+        write!(f, "R={},mu={},sigma={},dt={},b={}", self.radius, self.mu, self.sigma, self.dt, peaks.join(":"))
+    }
+}
+
+// The Orbium glider of the orbium world, as published with Lenia (heading down)
+const ORBIUM: [[f32; 20]; 20] = [
+    [0.00, 0.00, 0.00, 0.00, 0.00, 0.00, 0.10, 0.14, 0.10, 0.00, 0.00, 0.03, 0.03, 0.00, 0.00, 0.30, 0.00, 0.00, 0.00, 0.00],
+    [0.00, 0.00, 0.00, 0.00, 0.00, 0.08, 0.24, 0.30, 0.30, 0.18, 0.14, 0.15, 0.16, 0.15, 0.09, 0.20, 0.00, 0.00, 0.00, 0.00],
+    [0.00, 0.00, 0.00, 0.00, 0.00, 0.15, 0.34, 0.44, 0.46, 0.38, 0.18, 0.14, 0.11, 0.13, 0.19, 0.18, 0.45, 0.00, 0.00, 0.00],
+    [0.00, 0.00, 0.00, 0.00, 0.06, 0.13, 0.39, 0.50, 0.50, 0.37, 0.06, 0.00, 0.00, 0.00, 0.02, 0.16, 0.68, 0.00, 0.00, 0.00],
+    [0.00, 0.00, 0.00, 0.11, 0.17, 0.17, 0.33, 0.40, 0.38, 0.28, 0.14, 0.00, 0.00, 0.00, 0.00, 0.00, 0.18, 0.42, 0.00, 0.00],
+    [0.00, 0.00, 0.09, 0.18, 0.13, 0.06, 0.08, 0.26, 0.32, 0.32, 0.27, 0.00, 0.00, 0.00, 0.00, 0.00, 0.00, 0.82, 0.00, 0.00],
+    [0.27, 0.00, 0.16, 0.12, 0.00, 0.00, 0.00, 0.25, 0.38, 0.44, 0.45, 0.34, 0.00, 0.00, 0.00, 0.00, 0.00, 0.22, 0.17, 0.00],
+    [0.00, 0.07, 0.20, 0.02, 0.00, 0.00, 0.00, 0.31, 0.48, 0.57, 0.60, 0.57, 0.00, 0.00, 0.00, 0.00, 0.00, 0.00, 0.49, 0.00],
+    [0.00, 0.59, 0.19, 0.00, 0.00, 0.00, 0.00, 0.20, 0.57, 0.69, 0.76, 0.76, 0.49, 0.00, 0.00, 0.00, 0.00, 0.00, 0.36, 0.00],
+    [0.00, 0.58, 0.19, 0.00, 0.00, 0.00, 0.00, 0.00, 0.67, 0.83, 0.90, 0.92, 0.87, 0.12, 0.00, 0.00, 0.00, 0.00, 0.22, 0.07],
+    [0.00, 0.00, 0.46, 0.00, 0.00, 0.00, 0.00, 0.00, 0.70, 0.93, 1.00, 1.00, 1.00, 0.61, 0.00, 0.00, 0.00, 0.00, 0.18, 0.11],
+    [0.00, 0.00, 0.82, 0.00, 0.00, 0.00, 0.00, 0.00, 0.47, 1.00, 1.00, 0.98, 1.00, 0.96, 0.27, 0.00, 0.00, 0.00, 0.19, 0.10],
+    [0.00, 0.00, 0.46, 0.00, 0.00, 0.00, 0.00, 0.00, 0.25, 1.00, 1.00, 0.84, 0.92, 0.97, 0.54, 0.14, 0.04, 0.10, 0.21, 0.05],
+    [0.00, 0.00, 0.00, 0.40, 0.00, 0.00, 0.00, 0.00, 0.09, 0.80, 1.00, 0.82, 0.80, 0.85, 0.63, 0.31, 0.18, 0.19, 0.20, 0.01],
+    [0.00, 0.00, 0.00, 0.36, 0.10, 0.00, 0.00, 0.00, 0.05, 0.54, 0.86, 0.79, 0.74, 0.72, 0.60, 0.39, 0.28, 0.24, 0.13, 0.00],
+    [0.00, 0.00, 0.00, 0.01, 0.30, 0.07, 0.00, 0.00, 0.08, 0.36, 0.64, 0.70, 0.64, 0.60, 0.51, 0.39, 0.29, 0.19, 0.04, 0.00],
+    [0.00, 0.00, 0.00, 0.00, 0.10, 0.24, 0.14, 0.10, 0.15, 0.29, 0.45, 0.53, 0.52, 0.46, 0.40, 0.31, 0.21, 0.08, 0.00, 0.00],
+    [0.00, 0.00, 0.00, 0.00, 0.00, 0.08, 0.21, 0.21, 0.22, 0.29, 0.36, 0.39, 0.37, 0.33, 0.26, 0.18, 0.09, 0.00, 0.00, 0.00],
+    [0.00, 0.00, 0.00, 0.00, 0.00, 0.00, 0.03, 0.13, 0.19, 0.22, 0.24, 0.24, 0.23, 0.18, 0.13, 0.05, 0.00, 0.00, 0.00, 0.00],
+    [0.00, 0.00, 0.00, 0.00, 0.00, 0.00, 0.00, 0.00, 0.02, 0.06, 0.08, 0.09, 0.07, 0.05, 0.01, 0.00, 0.00, 0.00, 0.00, 0.00],
+];
+
+// Continuous cellular automaton on a toroidal size x size field of values in [0, 1].
+// Each step convolves the field with a ring kernel (through the FFT, so the cost
+// doesn't depend on the radius), maps the result through the growth function
+// and adds dt times that growth to every cell
+pub struct Lenia {
+    size: usize,
+    params: LeniaParams,
+    cells: Vec<f32>,
+    kernel: Vec<Complex>, // Fourier transform of the normalised kernel
+    fft: Fft2d,
+    time: f32,
+    seed: u64, // Seed of the starting field, so a start can be replayed
+}
+
+impl Lenia {
+    // size must be a power of two for the FFT
+    pub fn new(params: LeniaParams, size: usize, seed: u64) -> Self {
+        let fft = Fft2d::new(size);
+        let mut kernel = build_kernel(&params, size);
+        fft.transform(&mut kernel, false);
+        let mut lenia = Lenia { size, params, cells: vec![0.0; size * size], kernel, fft, time: 0.0, seed };
+        lenia.reseed(seed);
+        lenia
+    }
+
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    pub fn params(&self) -> &LeniaParams {
+        &self.params
+    }
+
+    pub fn time(&self) -> f32 {
+        self.time
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn value(&self, x: usize, y: usize) -> f32 {
+        self.cells[y * self.size + x]
+    }
+
+    // Total of all cell values, Lenia's "mass"
+    pub fn mass(&self) -> f32 {
+        self.cells.iter().sum()
+    }
+
+    // Clear the field and start over from a seed. The orbium world gets a few
+    // Orbiums facing random ways, since noise almost never settles into one;
+    // other worlds get a central square, a few kernels wide, of noise
+    pub fn reseed(&mut self, seed: u64) {
+        let mut rng = StdRng::seed_from_u64(seed);
+        self.seed = seed;
+        self.cells.fill(0.0);
+        self.time = 0.0;
+
+        if LeniaParams::preset("orbium").as_ref() == Some(&self.params) {
+            for i in 0..3 {
+                let (left, top) = (self.size * (1 + 2 * i) / 6, rng.gen_range(0..self.size));
+                let turns = rng.gen_range(0..4);
+                for (y, row) in ORBIUM.iter().enumerate() {
+                    for (x, &value) in row.iter().enumerate() {
+                        // Rotate by quarter turns inside the 20x20 box
+                        let (mut rx, mut ry) = (x, y);
+                        for _ in 0..turns {
+                            (rx, ry) = (19 - ry, rx);
+                        }
+                        let px = (left + rx) % self.size;
+                        let py = (top + ry) % self.size;
+                        self.cells[py * self.size + px] = value;
+                    }
+                }
+            }
+            return;
+        }
+
+        let side = ((self.params.radius * 4.0) as usize).min(self.size);
+        let start = (self.size - side) / 2;
+        for y in start..start + side {
+            for x in start..start + side {
+                self.cells[y * self.size + x] = rng.gen_range(0.0..1.0);
+            }
+        }
+    }
+
+    // Set every cell within a radius of (x, y) to a value, wrapping around the edges
+    pub fn paint(&mut self, x: usize, y: usize, radius: f32, value: f32) {
+        let reach = radius.ceil() as i64;
+        let n = self.size as i64;
+        for dy in -reach..=reach {
+            for dx in -reach..=reach {
+                if ((dx * dx + dy * dy) as f32).sqrt() <= radius {
+                    let px = (x as i64 + dx).rem_euclid(n) as usize;
+                    let py = (y as i64 + dy).rem_euclid(n) as usize;
+                    self.cells[py * self.size + px] = value;
+                }
+            }
+        }
+    }
+
+    pub fn step(&mut self) {
+        let mut field: Vec<Complex> = self.cells.iter().map(|&value| Complex::new(value as f64, 0.0)).collect();
+        self.fft.transform(&mut field, false);
+        for (value, weight) in field.iter_mut().zip(self.kernel.iter()) {
+            *value = *value * *weight;
+        }
+        self.fft.transform(&mut field, true);
+
+        let LeniaParams { mu, sigma, dt, .. } = self.params;
+        for (cell, potential) in self.cells.iter_mut().zip(field.iter()) {
+            let growth = growth(potential.re as f32, mu, sigma);
+            *cell = (*cell + dt * growth).clamp(0.0, 1.0);
+        }
+        self.time += dt;
+    }
+}
+
+// Bump shaped ring profile on (0, 1), peaking at 1 in the middle
+fn kernel_shell(r: f32) -> f32 {
+    if r <= 0.0 || r >= 1.0 { 0.0 } else { (4.0 - 1.0 / (r * (1.0 - r))).exp() }
+}
+
+// Gaussian growth: +1 when the neighbourhood potential is exactly mu, falling to -1 away from it
+fn growth(potential: f32, mu: f32, sigma: f32) -> f32 {
+    let distance = (potential - mu) / sigma;
+    2.0 * (-distance * distance / 2.0).exp() - 1.0
+}
+
+// Kernel laid out on the size x size torus with its centre at (0, 0), so the
+// convolution lines up with the cells, and normalised to sum to 1
+fn build_kernel(params: &LeniaParams, size: usize) -> Vec<Complex> {
+    let mut kernel = vec![Complex::default(); size * size];
+    let reach = params.radius.ceil() as i64;
+    let rings = params.peaks.len() as f32;
+    let mut total = 0.0;
+
+    for dy in -reach..=reach {
+        for dx in -reach..=reach {
+            let distance = ((dx * dx + dy * dy) as f32).sqrt() / params.radius;
+            if distance >= 1.0 {
+                continue;
+            }
+            // Which ring the cell falls in, and how far across that ring
+            let position = distance * rings;
+            let ring = position.floor() as usize;
+            let weight = params.peaks[ring] * kernel_shell(position - ring as f32);
+
+            let x = dx.rem_euclid(size as i64) as usize;
+            let y = dy.rem_euclid(size as i64) as usize;
+            kernel[y * size + x].re += weight as f64;
+            total += weight as f64;
+        }
+    }
+
+    if total > 0.0 {
+        for value in kernel.iter_mut() {
+            value.re /= total;
+        }
+    }
+    kernel
+}
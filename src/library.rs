@@ -0,0 +1,160 @@
+use crate::census::{Behaviour, Classifier};
+use crate::pattern::{self, Format, Pattern};
+
+// Patterns built into the program, by key
+const BUILTIN: [(&str, &str); 18] = [
+    ("block", include_str!("../patterns/block.rle")),
+    ("beehive", include_str!("../patterns/beehive.rle")),
+    ("loaf", include_str!("../patterns/loaf.rle")),
+    ("boat", include_str!("../patterns/boat.rle")),
+    ("tub", include_str!("../patterns/tub.rle")),
+    ("blinker", include_str!("../patterns/blinker.rle")),
+    ("toad", include_str!("../patterns/toad.rle")),
+    ("beacon", include_str!("../patterns/beacon.rle")),
+    ("pulsar", include_str!("../patterns/pulsar.rle")),
+    ("pentadecathlon", include_str!("../patterns/pentadecathlon.rle")),
+    ("glider", include_str!("../patterns/glider.rle")),
+    ("lwss", include_str!("../patterns/lwss.rle")),
+    ("mwss", include_str!("../patterns/mwss.rle")),
+    ("hwss", include_str!("../patterns/hwss.rle")),
+    ("gosper-glider-gun", include_str!("../patterns/gosper-glider-gun.rle")),
+    ("r-pentomino", include_str!("../patterns/r-pentomino.rle")),
+    ("acorn", include_str!("../patterns/acorn.rle")),
+    ("diehard", include_str!("../patterns/diehard.rle")),
+];
+
+// What kind of object a library pattern is
+#[derive(Clone, Copy, PartialEq)]
+pub enum Category {
+    StillLife,
+    Oscillator,
+    Spaceship,
+    Gun,        // Emits spaceships forever
+    Methuselah, // Small, but takes a long time to settle
+    Other,
+}
+
+impl Category {
+    pub fn parse(name: &str) -> Result<Self, String> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "still life" | "still-life" => Ok(Category::StillLife),
+            "oscillator" => Ok(Category::Oscillator),
+            "spaceship" => Ok(Category::Spaceship),
+            "gun" => Ok(Category::Gun),
+            "methuselah" => Ok(Category::Methuselah),
+            "other" => Ok(Category::Other),
+            _ => Err(format!("'{}' is not a category (use still life, oscillator, spaceship, gun, methuselah or other)", name)),
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Category::StillLife => "still life",
+            Category::Oscillator => "oscillator",
+            Category::Spaceship => "spaceship",
+            Category::Gun => "gun",
+            Category::Methuselah => "methuselah",
+            Category::Other => "other",
+        }
+    }
+}
+
+// A named pattern of the library. Its bounding box is the pattern's size
+pub struct Entry {
+    pub key: String,  // File name without the extension, lower case
+    pub name: String, // The name the file gives, or the key
+    pub category: Category,
+    pub period: Option<usize>, // Generations until it repeats, if it does
+    pub pattern: Pattern,
+}
+
+impl Entry {
+    // Category and period come from "#C Category: gun" and "#C Period: 30" comment
+    // lines. Without them, a Life pattern is run on its own to find out
+    fn new(key: &str, pattern: Pattern, classifier: &mut Classifier) -> Result<Self, String> {
+        let mut category = None;
+        let mut period = None;
+        for comment in &pattern.comments {
+            if let Some(name) = comment.strip_prefix("Category:") {
+                category = Some(Category::parse(name)?);
+            } else if let Some(n) = comment.strip_prefix("Period:") {
+                period = Some(n.trim().parse().map_err(|_| format!("bad period in \"{}\"", comment))?);
+            }
+        }
+
+        if category.is_none() && pattern::is_life(pattern.rule.as_deref()) {
+            let cells: Vec<(i64, i64)> = pattern
+                .cells
+                .iter()
+                .enumerate()
+                .flat_map(|(y, row)| row.iter().enumerate().filter(|(_, state)| **state != 0).map(move |(x, _)| (x as i64, y as i64)))
+                .collect();
+            (category, period) = match classifier.classify(&cells).1 {
+                Behaviour::Still => (Some(Category::StillLife), Some(1)),
+                Behaviour::Oscillator(p) => (Some(Category::Oscillator), Some(p)),
+                Behaviour::Spaceship { period: p, .. } => (Some(Category::Spaceship), Some(p)),
+                Behaviour::Unsettled => (None, period),
+            };
+        }
+
+        let name = pattern.name.clone().unwrap_or_else(|| key.to_string());
+        Ok(Entry { key: key.to_string(), name, category: category.unwrap_or(Category::Other), period, pattern })
+    }
+}
+
+// Named patterns to place, the built-in ones first
+pub struct Library {
+    entries: Vec<Entry>,
+    classifier: Classifier,
+}
+
+impl Library {
+    pub fn builtin() -> Self {
+        let mut library = Library { entries: Vec::new(), classifier: Classifier::default() };
+        for (key, text) in BUILTIN {
+            let entry = Format::Rle.parse(text).and_then(|pattern| Entry::new(key, pattern, &mut library.classifier));
+            match entry {
+                Ok(entry) => library.entries.push(entry),
+                Err(err) => panic!("built-in pattern {}: {}", key, err),
+            }
+        }
+        library
+    }
+
+    // Add every pattern file of a directory (by extension), replacing library patterns
+    // of the same key. Files that fail to load are skipped, and their errors returned
+    pub fn load_dir(&mut self, dir: &str) -> Result<Vec<String>, String> {
+        let mut paths: Vec<_> = std::fs::read_dir(dir)
+            .map_err(|err| format!("{}: {}", dir, err))?
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.is_file() && path.to_str().and_then(Format::from_extension).is_some())
+            .collect();
+        paths.sort();
+
+        let mut errors = Vec::new();
+        for path in paths {
+            let Some(key) = path.file_stem().and_then(|stem| stem.to_str()).map(str::to_ascii_lowercase) else {
+                continue;
+            };
+            let path = path.to_string_lossy();
+            let entry = pattern::load(&path).and_then(|pattern| Entry::new(&key, pattern, &mut self.classifier));
+            match entry {
+                Ok(entry) => match self.entries.iter_mut().find(|known| known.key == key) {
+                    Some(known) => *known = entry,
+                    None => self.entries.push(entry),
+                },
+                Err(err) => errors.push(format!("{}: {}", path, err)),
+            }
+        }
+        Ok(errors)
+    }
+
+    // A pattern by key or name, in any case
+    pub fn get(&self, name: &str) -> Option<&Entry> {
+        self.entries.iter().find(|entry| entry.key.eq_ignore_ascii_case(name) || entry.name.eq_ignore_ascii_case(name))
+    }
+
+    pub fn entries(&self) -> &[Entry] {
+        &self.entries
+    }
+}
@@ -0,0 +1,165 @@
+use crate::pattern::Pattern;
+
+// Read a Life 1.05 pattern: #D description lines, the rule as #N (plain Life)
+// or #R survival/birth, and blocks of . and * rows, each placed by the
+// "#P x y" line before it relative to the pattern centre
+pub fn parse_105(text: &str) -> Result<Pattern, String> {
+    let mut lines = text.lines();
+    if !lines.next().is_some_and(|line| line.trim().starts_with("#Life 1.05")) {
+        return Err("missing the #Life 1.05 header".to_string());
+    }
+
+    let mut comments = Vec::new();
+    let mut rule = None;
+    let mut points = Vec::new();
+    let (mut left, mut y) = (0i64, 0i64);
+    for line in lines {
+        let line = line.trim();
+        if let Some(description) = line.strip_prefix("#D") {
+            comments.push(description.trim().to_string());
+        } else if line.starts_with("#N") {
+            rule = Some("B3/S23".to_string());
+        } else if let Some(spec) = line.strip_prefix("#R") {
+            let (survival, birth) = spec.trim().split_once('/').ok_or(format!("expected #R survival/birth, found \"{}\"", line))?;
+            rule = Some(format!("B{}/S{}", birth.trim(), survival.trim()));
+        } else if let Some(offset) = line.strip_prefix("#P") {
+            let numbers: Vec<i64> = offset.split_whitespace().map(|n| n.parse().map_err(|_| format!("bad offset in \"{}\"", line))).collect::<Result<_, String>>()?;
+            let [x, top] = numbers[..] else {
+                return Err(format!("expected #P x y, found \"{}\"", line));
+            };
+            (left, y) = (x, top);
+        } else if line.starts_with('#') || line.is_empty() {
+            continue;
+        } else {
+            for (i, c) in line.chars().enumerate() {
+                match c {
+                    '.' => {},
+                    '*' | 'O' => {
+                        let x = left.checked_add(i as i64).ok_or(format!("\"{}\" runs past the edge of the plane", line))?;
+                        points.push((x, y, 1));
+                    },
+                    c => return Err(format!("unexpected '{}' in \"{}\"", c, line)),
+                }
+            }
+            y = y.checked_add(1).ok_or("rows run past the edge of the plane".to_string())?;
+        }
+    }
+
+    let mut pattern = Pattern::from_points(&points)?;
+    pattern.comments = comments;
+    pattern.rule = rule;
+    Ok(pattern)
+}
+
+// Read a Life 1.06 pattern: the #Life 1.06 header and an "x y" line per live cell
+pub fn parse_106(text: &str) -> Result<Pattern, String> {
+    let mut lines = text.lines();
+    if !lines.next().is_some_and(|line| line.trim().starts_with("#Life 1.06")) {
+        return Err("missing the #Life 1.06 header".to_string());
+    }
+
+    let mut points = Vec::new();
+    for line in lines.map(str::trim).filter(|line| !line.is_empty() && !line.starts_with('#')) {
+        let mut numbers = line.split_whitespace().map(|n| n.parse::<i64>());
+        match (numbers.next(), numbers.next(), numbers.next()) {
+            (Some(Ok(x)), Some(Ok(y)), None) => points.push((x, y, 1)),
+            _ => return Err(format!("expected x y, found \"{}\"", line)),
+        }
+    }
+    Pattern::from_points(&points)
+}
+
+// Write a pattern as Life 1.05, in one block centred on the origin. Only Life-like
+// rules can be written, and every live state is written as *
+pub fn write_105(pattern: &Pattern) -> String {
+    let mut text = "#Life 1.05\n".to_string();
+    if let Some(name) = &pattern.name {
+        text += &format!("#D {}\n", name);
+    }
+    if let Some(author) = &pattern.author {
+        text += &format!("#D {}\n", author);
+    }
+    for comment in &pattern.comments {
+        text += &format!("#D {}\n", comment);
+    }
+    match pattern.rule.as_deref().and_then(life_like) {
+        Some((birth, survival)) if birth == "3" && survival == "23" => text += "#N\n",
+        Some((birth, survival)) => text += &format!("#R {}/{}\n", survival, birth),
+        None => {},
+    }
+    text += &format!("#P {} {}\n", -(pattern.width() as i64 / 2), -(pattern.height() as i64 / 2));
+    for row in &pattern.cells {
+        let length = row.iter().rposition(|&state| state != 0).map_or(0, |last| last + 1);
+        if length == 0 {
+            text += ".";
+        }
+        text.extend(row[..length].iter().map(|&state| if state == 0 { '.' } else { '*' }));
+        text += "\n";
+    }
+    text
+}
+
+// Write a pattern as Life 1.06, with coordinates from its top-left corner.
+// The format has no room for comments or the rule
+pub fn write_106(pattern: &Pattern) -> String {
+    let mut text = "#Life 1.06\n".to_string();
+    for (y, row) in pattern.cells.iter().enumerate() {
+        for (x, _) in row.iter().enumerate().filter(|(_, state)| **state != 0) {
+            text += &format!("{} {}\n", x, y);
+        }
+    }
+    text
+}
+
+// Birth and survival digits of a rule written like B3/S23
+fn life_like(rule: &str) -> Option<(&str, &str)> {
+    let (birth, survival) = rule.split_once('/')?;
+    let birth = birth.strip_prefix(['B', 'b'])?;
+    let survival = survival.strip_prefix(['S', 's'])?;
+    let digits = |part: &str| part.chars().all(|c| c.is_ascii_digit());
+    (digits(birth) && digits(survival)).then_some((birth, survival))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn glider(rule: Option<&str>) -> Pattern {
+        Pattern {
+            comments: vec!["The smallest spaceship".to_string()],
+            rule: rule.map(str::to_string),
+            cells: vec![vec![0, 1, 0], vec![0, 0, 1], vec![1, 1, 1]],
+            ..Pattern::default()
+        }
+    }
+
+    #[test]
+    fn life_105_round_trips() {
+        for rule in [None, Some("B3/S23"), Some("B36/S23")] {
+            let pattern = glider(rule);
+            let read = parse_105(&write_105(&pattern)).unwrap();
+            assert_eq!(read.cells, pattern.cells);
+            assert_eq!(read.rule, pattern.rule);
+            assert_eq!(read.comments, pattern.comments);
+        }
+        // An empty row in the middle is kept
+        let pattern = Pattern { cells: vec![vec![1, 1], vec![0, 0], vec![0, 1]], ..Pattern::default() };
+        assert_eq!(parse_105(&write_105(&pattern)).unwrap().cells, pattern.cells);
+    }
+
+    #[test]
+    fn life_106_round_trips() {
+        let pattern = glider(None);
+        assert_eq!(parse_106(&write_106(&pattern)).unwrap().cells, pattern.cells);
+        let read = parse_106("#Life 1.06\n-5 -5\n-3 -4\n").unwrap();
+        assert_eq!(read.cells, vec![vec![1, 0, 0], vec![0, 0, 1]]);
+    }
+
+    #[test]
+    fn cells_past_the_edge_are_rejected() {
+        assert!(parse_106("#Life 1.06\n-9223372036854775808 0\n9223372036854775807 0\n").is_err());
+        assert!(parse_105("#Life 1.05\n#P 9223372036854775806 0\n***\n").is_err());
+        assert!(parse_105("#Life 1.05\n#P 0 9223372036854775807\n*\n*\n").is_err());
+        assert!(parse_105("#Life 1.05\n#P 9223372036854775806 0\n.*\n").is_ok());
+    }
+}
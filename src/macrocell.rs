@@ -0,0 +1,283 @@
+use std::collections::HashMap;
+use crate::pattern::{Pattern, MAX_CELLS};
+
+// Deepest tree read; a level n node covers 2^n x 2^n cells, so coordinates stay in i64
+const MAX_LEVEL: u8 = 62;
+
+// A node line of a macrocell file
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Node {
+    Cells([u8; 8]),         // Level 3 leaf of a two-state file: 8 rows, bit x set for a live cell in column x
+    States([u8; 4]),        // Level 1 leaf of a multi-state file: states of the nw, ne, sw and se cells
+    Branch(u8, [usize; 4]), // Level and nw, ne, sw, se children, as positions of earlier nodes (0 is empty)
+}
+
+impl Node {
+    pub fn level(&self) -> u8 {
+        match self {
+            Node::Cells(_) => 3,
+            Node::States(_) => 1,
+            Node::Branch(level, _) => *level,
+        }
+    }
+}
+
+// A Golly macrocell (.mc) file: a quadtree written bottom-up, one node per
+// line, each identical subtree written once. The last node is the root, and
+// the root's centre is the origin of the plane
+#[derive(Default)]
+pub struct Macrocell {
+    pub rule: Option<String>,
+    pub generation: u64,
+    pub comments: Vec<String>,
+    pub nodes: Vec<Node>, // Node n of the file (counting from 1) is nodes[n - 1]
+}
+
+impl Macrocell {
+    // Read the [M2] header, #R rule, #G generation and #C comment lines, then the
+    // nodes: 8x8 leaves like ".*$..*$***$" in two-state files, "1 a b c d" leaves
+    // of four states in multi-state ones, and "level nw ne sw se" branches
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut lines = text.lines();
+        if !lines.next().is_some_and(|line| line.trim().starts_with("[M2]")) {
+            return Err("missing the [M2] header".to_string());
+        }
+
+        let mut macrocell = Macrocell::default();
+        for line in lines.map(str::trim).filter(|line| !line.is_empty()) {
+            if let Some(rule) = line.strip_prefix("#R") {
+                macrocell.rule = Some(rule.trim().to_string());
+            } else if let Some(generation) = line.strip_prefix("#G") {
+                macrocell.generation = generation.trim().parse().map_err(|_| format!("bad generation in \"{}\"", line))?;
+            } else if let Some(comment) = line.strip_prefix("#C").or_else(|| line.strip_prefix("#c")) {
+                macrocell.comments.push(comment.trim().to_string());
+            } else if line.starts_with('#') {
+                continue;
+            } else {
+                let node = parse_node(line)?;
+                // Children must be earlier nodes one level down
+                if let Node::Branch(level, children) = node {
+                    for child in children.into_iter().filter(|&child| child != 0) {
+                        match macrocell.nodes.get(child - 1) {
+                            Some(below) if below.level() + 1 == level => {},
+                            Some(_) => return Err(format!("node {} is not one level below \"{}\"", child, line)),
+                            None => return Err(format!("node {} used before it is defined in \"{}\"", child, line)),
+                        }
+                    }
+                }
+                macrocell.nodes.push(node);
+            }
+        }
+
+        let two_state = macrocell.nodes.iter().any(|node| matches!(node, Node::Cells(_)));
+        if two_state && macrocell.nodes.iter().any(|node| matches!(node, Node::States(_))) {
+            return Err("mixes two-state and multi-state leaves".to_string());
+        }
+        Ok(macrocell)
+    }
+
+    // Build the tree of a pattern, with its top-left corner at the top-left of the root
+    pub fn from_pattern(pattern: &Pattern) -> Self {
+        let two_states = pattern.cells.iter().flatten().all(|&state| state <= 1);
+        let leaf_level = if two_states { 3 } else { 1 };
+        let mut level = leaf_level;
+        while (1usize << level) < pattern.width().max(pattern.height()) {
+            level += 1;
+        }
+
+        // Macrocell files have no name or author lines, so those go in the comments
+        let comments = pattern.name.iter().chain(&pattern.author).chain(&pattern.comments).cloned().collect();
+        let mut macrocell = Macrocell { rule: pattern.rule.clone(), comments, ..Macrocell::default() };
+        let mut index = HashMap::new();
+        macrocell.build(pattern, &mut index, leaf_level, level, 0, 0);
+        macrocell
+    }
+
+    pub fn two_states(&self) -> bool {
+        !self.nodes.iter().any(|node| matches!(node, Node::States(_)))
+    }
+
+    // Add a node, or find the same one already added; returns its position
+    pub fn push(&mut self, node: Node, index: &mut HashMap<Node, usize>) -> usize {
+        *index.entry(node).or_insert_with(|| {
+            self.nodes.push(node);
+            self.nodes.len()
+        })
+    }
+
+    // The live cells as a pattern, which has to be small enough to hold cell by cell
+    pub fn to_pattern(&self) -> Result<Pattern, String> {
+        let mut points = Vec::new();
+        if let Some(root) = self.nodes.last() {
+            let half = 1i64 << (root.level() - 1);
+            self.collect(self.nodes.len(), -half, -half, &mut points)?;
+        }
+        let mut pattern = Pattern::from_points(&points)?;
+        pattern.rule = self.rule.clone();
+        pattern.comments = self.comments.clone();
+        Ok(pattern)
+    }
+
+    pub fn write(&self) -> String {
+        let mut text = "[M2]\n".to_string();
+        if let Some(rule) = &self.rule {
+            text += &format!("#R {}\n", rule);
+        }
+        if self.generation != 0 {
+            text += &format!("#G {}\n", self.generation);
+        }
+        for comment in &self.comments {
+            text += &format!("#C {}\n", comment);
+        }
+        for node in &self.nodes {
+            match node {
+                Node::Cells(rows) => {
+                    // Each row ends in $; dead cells at row ends and empty rows at the end are left out
+                    let used = rows.iter().rposition(|&row| row != 0).map_or(0, |last| last + 1);
+                    for &row in &rows[..used] {
+                        let length = 8 - row.leading_zeros() as usize;
+                        text.extend((0..length).map(|x| if row & (1 << x) != 0 { '*' } else { '.' }));
+                        text += "$";
+                    }
+                },
+                Node::States([nw, ne, sw, se]) => text += &format!("1 {} {} {} {}", nw, ne, sw, se),
+                Node::Branch(level, [nw, ne, sw, se]) => text += &format!("{} {} {} {} {}", level, nw, ne, sw, se),
+            }
+            text += "\n";
+        }
+        text
+    }
+
+    // Node for the square of a pattern at (x0, y0), 0 when it's empty
+    fn build(&mut self, pattern: &Pattern, index: &mut HashMap<Node, usize>, leaf_level: u8, level: u8, x0: usize, y0: usize) -> usize {
+        if x0 >= pattern.width() || y0 >= pattern.height() {
+            return 0;
+        }
+        let state = |x: usize, y: usize| pattern.cells.get(y).and_then(|row| row.get(x)).copied().unwrap_or(0);
+        let node = if level == leaf_level && leaf_level == 3 {
+            let mut rows = [0u8; 8];
+            for (y, row) in rows.iter_mut().enumerate() {
+                *row = (0..8).filter(|&x| state(x0 + x, y0 + y) != 0).fold(0, |bits, x| bits | 1 << x);
+            }
+            Node::Cells(rows)
+        } else if level == leaf_level {
+            Node::States([state(x0, y0), state(x0 + 1, y0), state(x0, y0 + 1), state(x0 + 1, y0 + 1)])
+        } else {
+            let half = 1 << (level - 1);
+            let nw = self.build(pattern, index, leaf_level, level - 1, x0, y0);
+            let ne = self.build(pattern, index, leaf_level, level - 1, x0 + half, y0);
+            let sw = self.build(pattern, index, leaf_level, level - 1, x0, y0 + half);
+            let se = self.build(pattern, index, leaf_level, level - 1, x0 + half, y0 + half);
+            Node::Branch(level, [nw, ne, sw, se])
+        };
+        match node {
+            Node::Cells([0, 0, 0, 0, 0, 0, 0, 0]) | Node::States([0, 0, 0, 0]) | Node::Branch(_, [0, 0, 0, 0]) => 0,
+            node => self.push(node, index),
+        }
+    }
+
+    // Gather the live cells of node n with its top-left corner at (x0, y0)
+    fn collect(&self, n: usize, x0: i64, y0: i64, points: &mut Vec<(i64, i64, u8)>) -> Result<(), String> {
+        if n == 0 {
+            return Ok(());
+        }
+        match self.nodes[n - 1] {
+            Node::Cells(rows) => {
+                for (y, row) in rows.iter().enumerate() {
+                    points.extend((0..8).filter(|x| row & (1 << x) != 0).map(|x| (x0 + x, y0 + y as i64, 1)));
+                }
+            },
+            Node::States(states) => {
+                for (i, state) in states.into_iter().enumerate().filter(|&(_, state)| state != 0) {
+                    points.push((x0 + (i % 2) as i64, y0 + (i / 2) as i64, state));
+                }
+            },
+            Node::Branch(level, children) => {
+                let half = 1i64 << (level - 1);
+                for (i, child) in children.into_iter().enumerate() {
+                    self.collect(child, x0 + (i % 2) as i64 * half, y0 + (i / 2) as i64 * half, points)?;
+                }
+            },
+        }
+        if points.len() > MAX_CELLS {
+            return Err("too many live cells to place; open it on the HashLife board instead".to_string());
+        }
+        Ok(())
+    }
+}
+
+fn parse_node(line: &str) -> Result<Node, String> {
+    if line.starts_with(['.', '*', '$']) {
+        let mut rows = [0u8; 8];
+        let (mut x, mut y) = (0, 0);
+        for c in line.chars() {
+            match c {
+                '.' | '*' if x >= 8 || y >= 8 => return Err(format!("leaf wider or taller than 8 cells in \"{}\"", line)),
+                '.' => x += 1,
+                '*' => {
+                    rows[y] |= 1 << x;
+                    x += 1;
+                },
+                '$' => (x, y) = (0, y + 1),
+                c => return Err(format!("unexpected '{}' in \"{}\"", c, line)),
+            }
+        }
+        return Ok(Node::Cells(rows));
+    }
+
+    let numbers: Vec<u64> = line.split_whitespace().map(|n| n.parse().map_err(|_| format!("bad node \"{}\"", line))).collect::<Result<_, String>>()?;
+    match numbers[..] {
+        [1, nw, ne, sw, se] => {
+            let state = |n: u64| u8::try_from(n).map_err(|_| format!("state {} above 255 in \"{}\"", n, line));
+            Ok(Node::States([state(nw)?, state(ne)?, state(sw)?, state(se)?]))
+        },
+        [level, nw, ne, sw, se] if (2..=MAX_LEVEL as u64).contains(&level) => {
+            Ok(Node::Branch(level as u8, [nw as usize, ne as usize, sw as usize, se as usize]))
+        },
+        [level, ..] if level > MAX_LEVEL as u64 || level == 0 => Err(format!("node level {} out of range", level)),
+        _ => Err(format!("expected level and four children, found \"{}\"", line)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Write a pattern as a macrocell file and read it back
+    fn round_trip(pattern: &Pattern) -> Pattern {
+        let text = Macrocell::from_pattern(pattern).write();
+        Macrocell::parse(&text).and_then(|macrocell| macrocell.to_pattern()).unwrap()
+    }
+
+    #[test]
+    fn two_states_round_trip() {
+        // Wider than a leaf, so the tree has branches, and live on every edge
+        let mut cells = vec![vec![0; 20]; 11];
+        for (x, y) in [(0, 5), (3, 0), (4, 1), (2, 2), (3, 2), (4, 2), (19, 10), (12, 7), (13, 7)] {
+            cells[y][x] = 1;
+        }
+        let pattern = Pattern { name: Some("Test".to_string()), rule: Some("B3/S23".to_string()), cells, ..Pattern::default() };
+        let read = round_trip(&pattern);
+        assert_eq!(read.cells, pattern.cells);
+        assert_eq!(read.rule, pattern.rule);
+        assert_eq!(read.comments, vec!["Test".to_string()]);
+    }
+
+    #[test]
+    fn multi_state_round_trip() {
+        let cells = vec![vec![1, 0, 0, 2, 0], vec![0, 3, 0, 0, 0], vec![0, 0, 0, 255, 4]];
+        let pattern = Pattern { rule: Some("/2/3".to_string()), cells, ..Pattern::default() };
+        let macrocell = Macrocell::from_pattern(&pattern);
+        assert!(!macrocell.two_states());
+        let read = round_trip(&pattern);
+        assert_eq!(read.cells, pattern.cells);
+        assert_eq!(read.rule, pattern.rule);
+    }
+
+    #[test]
+    fn bad_files_are_rejected() {
+        assert!(Macrocell::parse("#R B3/S23\n").is_err());
+        assert!(Macrocell::parse("[M2]\n4 1 0 0 0\n").is_err());
+        assert!(Macrocell::parse("[M2]\n*$\n5 1 0 0 0\n").is_err());
+    }
+}
@@ -1,12 +1,61 @@
 mod framebuffer;
 mod line;
+mod hashlife;
+mod sparse;
+mod generations;
+mod hensel;
+mod neighborhood;
+mod tiling;
+mod automaton;
+mod ant;
+mod elementary;
+mod fft;
+mod lenia;
+mod stochastic;
+mod soup;
+mod margolus;
+mod history;
+mod cycle;
+mod census;
+mod ships;
+mod pattern;
+mod rle;
+mod plaintext;
+mod lif;
+mod macrocell;
+mod library;
+mod transform;
+mod scenario;
 
 use std::{thread, time::Duration};
 use raylib::prelude::*;
 use framebuffer::Framebuffer;
-use rand::{thread_rng, Rng};
+use hashlife::HashLife;
+use sparse::SparseBoard;
+use generations::{GenerationsBoard, GenerationsRule};
+use neighborhood::Neighborhood;
+use tiling::{Layout, Tiling};
+use automaton::{majority, AutomatonBoard, CellularAutomaton, Conway};
+use hensel::NEIGHBOR_OFFSETS;
+use ant::AntBoard;
+use elementary::{Rule1D, SpaceTime};
+use lenia::{Lenia, LeniaParams};
+use stochastic::StochasticRule;
+use soup::{Soup, Symmetry};
+use margolus::MargolusRule;
+use history::History;
+use cycle::{Cycle, CycleDetector};
+use census::{Behaviour, BehaviourTracker, Classifier, SeenObject};
+use ships::ShipTracker;
+use pattern::{Format, Pattern};
+use macrocell::Macrocell;
+use library::{Category, Entry, Library};
+use transform::{Orientation, Transform, MAX_PHASE};
+use scenario::{Scenario, Topology};
+use std::hash::{DefaultHasher, Hash, Hasher};
 
-// Grid dimensions for the Game of Life (use a lower resolution as suggested)
+// Grid dimensions for the Game of Life (use a lower resolution as suggested);
+// a scenario file may ask for others
 const GRID_WIDTH: u32 = 100;
 const GRID_HEIGHT: u32 = 100;
 const CELL_SIZE: u32 = 6; // Size of each cell in the grid for display
@@ -20,285 +69,111 @@ const OSCILLATOR_COLOR: Color = Color::LIME;      // Color for oscillators
 const SPACESHIP_COLOR: Color = Color::RED;        // Color for spaceships
 const CUSTOM_COLOR: Color = Color::SKYBLUE;       // Color for custom patterns
 const GENERATED_COLOR: Color = Color::ORANGE;     // Color for dynamically generated cells
+const DECAY_COLOR: Color = Color::MAGENTA;        // First refractory state of Generations rules, fading to the background
+const ANT_COLOR: Color = Color::WHITE;            // Direction markers of Langton's ants and turmites
+const SHIP_MARKER_COLOR: Color = Color::WHITE;    // Heading arrows and trails of tracked spaceships
+const PREVIEW_COLOR: Color = Color::LIGHTGRAY;    // Cells of a pattern about to be placed
 
-// Function to create a block pattern at a specific position
-fn create_block(grid: &mut Vec<Vec<bool>>, x: usize, y: usize) {
-    if x + 1 < GRID_WIDTH as usize && y + 1 < GRID_HEIGHT as usize {
-        grid[y][x] = true;
-        grid[y][x+1] = true;
-        grid[y+1][x] = true;
-        grid[y+1][x+1] = true;
-    }
-}
+// Colour map for Lenia, from empty (0.0) to full (1.0) cells
+const LENIA_COLOR_MAP: [Color; 5] = [BACKGROUND_COLOR, Color::DARKBLUE, Color::SKYBLUE, Color::GOLD, Color::WHITE];
 
-// Function to create a beehive pattern at a specific position
-fn create_beehive(grid: &mut Vec<Vec<bool>>, x: usize, y: usize) {
-    if x + 3 < GRID_WIDTH as usize && y + 2 < GRID_HEIGHT as usize {
-        grid[y][x+1] = true;
-        grid[y][x+2] = true;
-        grid[y+1][x] = true;
-        grid[y+1][x+3] = true;
-        grid[y+2][x+1] = true;
-        grid[y+2][x+2] = true;
-    }
-}
+// Colours painted by ants, cycled when a turmite rule has more colours than this
+const ANT_TRAIL_COLORS: [Color; 5] = [GENERATED_COLOR, STILL_LIFE_COLOR, OSCILLATOR_COLOR, SPACESHIP_COLOR, CUSTOM_COLOR];
 
-// Function to create a loaf pattern at a specific position
-fn create_loaf(grid: &mut Vec<Vec<bool>>, x: usize, y: usize) {
-    if x + 3 < GRID_WIDTH as usize && y + 3 < GRID_HEIGHT as usize {
-        grid[y][x+1] = true;
-        grid[y][x+2] = true;
-        grid[y+1][x] = true;
-        grid[y+1][x+3] = true;
-        grid[y+2][x+1] = true;
-        grid[y+2][x+3] = true;
-        grid[y+3][x+2] = true;
-    }
-}
+// HashLife backend configuration
+const HASHLIFE_MAX_NODES: usize = 4_000_000; // Garbage-collect the quadtree above this many nodes
+const MAX_STEP_EXPONENT: u8 = 30;            // Largest jump per frame is 2^30 generations
+const MAX_ANT_STEP_EXPONENT: u8 = 16;        // Ants are stepped one move at a time, so cap them at 2^16 per frame
 
-// Function to create a boat pattern at a specific position
-fn create_boat(grid: &mut Vec<Vec<bool>>, x: usize, y: usize) {
-    if x + 2 < GRID_WIDTH as usize && y + 2 < GRID_HEIGHT as usize {
-        grid[y][x] = true;
-        grid[y][x+1] = true;
-        grid[y+1][x] = true;
-        grid[y+1][x+2] = true;
-        grid[y+2][x+1] = true;
-    }
-}
+// Generations kept for rewinding the grid
+const HISTORY_LENGTH: usize = 2000;
 
-// Function to create a tub pattern at a specific position
-fn create_tub(grid: &mut Vec<Vec<bool>>, x: usize, y: usize) {
-    if x + 2 < GRID_WIDTH as usize && y + 2 < GRID_HEIGHT as usize {
-        grid[y][x+1] = true;
-        grid[y+1][x] = true;
-        grid[y+1][x+2] = true;
-        grid[y+2][x+1] = true;
-    }
-}
+// Board hashes kept for cycle detection, so periods up to this long are noticed
+const CYCLE_WINDOW: usize = 10_000;
 
-// Function to create a blinker pattern at a specific position (oscillator)
-fn create_blinker(grid: &mut Vec<Vec<bool>>, x: usize, y: usize) {
-    if x + 2 < GRID_WIDTH as usize && y < GRID_HEIGHT as usize {
-        grid[y][x] = true;
-        grid[y][x+1] = true;
-        grid[y][x+2] = true;
-    }
-}
+// Object census overlay: the most common kinds listed, and where the census is saved
+const CENSUS_LINES: usize = 12;
+const CENSUS_FILE: &str = "census.txt";
+// Every key, in the panel F1 shows; the status bar only has room for a few
+const HELP_LINES: [&str; 18] = [
+    "SPACE       Pause / run",
+    "S           Step one generation",
+    "R / N       Reset / reset with a new soup",
+    "UP / DOWN   Faster / slower",
+    "LEFT/RIGHT  Back / forward in the history",
+    "H / U       HashLife / unbounded board",
+    "[ / ]       Fewer / more generations a step",
+    "I J K L     Move the camera",
+    "G           Generations rules",
+    "C           Colour inheritance",
+    "T           Cell shape",
+    "O / E       Show / save the census",
+    "V           Ship tracking",
+    "P / TAB     Place a pattern / the next one",
+    "Z X A Q     Rotate, flip, phase, as loaded",
+    "W           Save the board",
+    "Mouse       Draw / erase",
+    "F1          Hide this help",
+];
+// Where W saves the board unless --save says otherwise; the extension picks the format
+const SAVED_PATTERN_FILE: &str = "board.rle";
+const LENIA_SIZE: usize = 256;               // Side of the Lenia field; a power of two for the FFT
 
-// Function to create a toad pattern at a specific position (oscillator)
-fn create_toad(grid: &mut Vec<Vec<bool>>, x: usize, y: usize) {
-    if x + 3 < GRID_WIDTH as usize && y + 1 < GRID_HEIGHT as usize {
-        grid[y][x+1] = true;
-        grid[y][x+2] = true;
-        grid[y][x+3] = true;
-        grid[y+1][x] = true;
-        grid[y+1][x+1] = true;
-        grid[y+1][x+2] = true;
-    }
-}
+// Cells the camera moves per key press on the unbounded backends
+const CAMERA_STEP: i64 = 10;
 
-// Function to create a beacon pattern at a specific position (oscillator)
-fn create_beacon(grid: &mut Vec<Vec<bool>>, x: usize, y: usize) {
-    if x + 3 < GRID_WIDTH as usize && y + 3 < GRID_HEIGHT as usize {
-        // Top-left block
-        grid[y][x] = true;
-        grid[y][x+1] = true;
-        grid[y+1][x] = true;
-        grid[y+1][x+1] = true;
-        
-        // Bottom-right block
-        grid[y+2][x+2] = true;
-        grid[y+2][x+3] = true;
-        grid[y+3][x+2] = true;
-        grid[y+3][x+3] = true;
-    }
-}
-
-// Function to create a glider pattern at a specific position (spaceship)
-fn create_glider(grid: &mut Vec<Vec<bool>>, x: usize, y: usize) {
-    if x + 2 < GRID_WIDTH as usize && y + 2 < GRID_HEIGHT as usize {
-        grid[y][x+1] = true;
-        grid[y+1][x+2] = true;
-        grid[y+2][x] = true;
-        grid[y+2][x+1] = true;
-        grid[y+2][x+2] = true;
-    }
-}
-
-// Function to create a lightweight spaceship (LWSS)
-fn create_lwss(grid: &mut Vec<Vec<bool>>, x: usize, y: usize) {
-    if x + 4 < GRID_WIDTH as usize && y + 3 < GRID_HEIGHT as usize {
-        grid[y][x+1] = true;
-        grid[y][x+4] = true;
-        grid[y+1][x] = true;
-        grid[y+2][x] = true;
-        grid[y+2][x+4] = true;
-        grid[y+3][x] = true;
-        grid[y+3][x+1] = true;
-        grid[y+3][x+2] = true;
-        grid[y+3][x+3] = true;
-    }
-}
-
-// Middle-weight spaceship (MWSS)
-fn create_mwss(grid: &mut Vec<Vec<bool>>, x: usize, y: usize) {
-    if x + 6 < GRID_WIDTH as usize && y + 5 < GRID_HEIGHT as usize {
-        // Head
-        grid[y][x+1] = true;
-        grid[y][x+4] = true;
-        // Body
-        grid[y+1][x] = true;
-        grid[y+2][x] = true;
-        grid[y+3][x] = true;
-        grid[y+3][x+5] = true;
-        grid[y+2][x+5] = true;
-        grid[y+1][x+5] = true;
-        // Tail
-        grid[y+4][x+1] = true;
-        grid[y+4][x+2] = true;
-        grid[y+4][x+3] = true;
-        grid[y+4][x+4] = true;
-    }
-}
-
-// Heavy-weight spaceship (HWSS)
-fn create_hwss(grid: &mut Vec<Vec<bool>>, x: usize, y: usize) {
-    if x + 7 < GRID_WIDTH as usize && y + 5 < GRID_HEIGHT as usize {
-        // Head
-        grid[y][x+1] = true;
-        grid[y][x+2] = true;
-        grid[y][x+3] = true;
-        grid[y][x+4] = true;
-        grid[y][x+5] = true;
-        grid[y][x+6] = true;
-        // Body
-        grid[y+1][x] = true;
-        grid[y+2][x] = true;
-        grid[y+3][x] = true;
-        grid[y+4][x+1] = true;
-        grid[y+4][x+2] = true;
-        grid[y+4][x+3] = true;
-        grid[y+4][x+4] = true;
-        grid[y+4][x+5] = true;
-        // Tail at sides
-        grid[y+1][x+6] = true;
-        grid[y+2][x+6] = true;
-        grid[y+3][x+6] = true;
-    }
-}
-
-// Function to create a pulsar pattern (large oscillator)
-fn create_pulsar(grid: &mut Vec<Vec<bool>>, x: usize, y: usize) {
-    if x + 12 < GRID_WIDTH as usize && y + 12 < GRID_HEIGHT as usize {
-        // Horizontal bars
-        for i in [2, 3, 4, 8, 9, 10] {
-            for j in [0, 5, 7, 12] {
-                grid[y+j][x+i] = true;
-                grid[y+i][x+j] = true;
-            }
-        }
-    }
-}
-
-// Function to create a Gosper glider gun (creates endless gliders)
-fn create_gosper_glider_gun(grid: &mut Vec<Vec<bool>>, x: usize, y: usize) {
-    if x + 36 < GRID_WIDTH as usize && y + 9 < GRID_HEIGHT as usize {
-        // Left block
-        grid[y+4][x+0] = true;
-        grid[y+4][x+1] = true;
-        grid[y+5][x+0] = true;
-        grid[y+5][x+1] = true;
-
-        // Left ship
-        grid[y+2][x+12] = true;
-        grid[y+2][x+13] = true;
-        grid[y+3][x+11] = true;
-        grid[y+3][x+15] = true;
-        grid[y+4][x+10] = true;
-        grid[y+4][x+16] = true;
-        grid[y+5][x+10] = true;
-        grid[y+5][x+14] = true;
-        grid[y+5][x+16] = true;
-        grid[y+5][x+17] = true;
-        grid[y+6][x+10] = true;
-        grid[y+6][x+16] = true;
-        grid[y+7][x+11] = true;
-        grid[y+7][x+15] = true;
-        grid[y+8][x+12] = true;
-        grid[y+8][x+13] = true;
-
-        // Right ship
-        grid[y+0][x+24] = true;
-        grid[y+1][x+22] = true;
-        grid[y+1][x+24] = true;
-        grid[y+2][x+20] = true;
-        grid[y+2][x+21] = true;
-        grid[y+3][x+20] = true;
-        grid[y+3][x+21] = true;
-        grid[y+4][x+20] = true;
-        grid[y+4][x+21] = true;
-        grid[y+5][x+22] = true;
-        grid[y+5][x+24] = true;
-        grid[y+6][x+24] = true;
-
-        // Right block
-        grid[y+2][x+34] = true;
-        grid[y+2][x+35] = true;
-        grid[y+3][x+34] = true;
-        grid[y+3][x+35] = true;
-    }
-}
-
-// Function to create a penta-decathlon (period 15 oscillator)
-fn create_pentadecathlon(grid: &mut Vec<Vec<bool>>, x: usize, y: usize) {
-    if x + 2 < GRID_WIDTH as usize && y + 9 < GRID_HEIGHT as usize {
-        // Central pattern
-        for i in 0..8 {
-            grid[y+i+1][x+1] = true;
-        }
-        
-        // Top and bottom "bumps"
-        grid[y][x] = true;
-        grid[y][x+2] = true;
-        grid[y+9][x] = true;
-        grid[y+9][x+2] = true;
-    }
-}
+// Library patterns on the starting board, by name and top-left cell: still lifes
+// in the top-left quadrant, oscillators in the top-right, spaceships in the
+// bottom-left and the gun and methuselahs around the rest
+const INITIAL_PATTERNS: [(&str, usize, usize); 23] = [
+    ("block", 10, 10),
+    ("beehive", 20, 15),
+    ("loaf", 30, 10),
+    ("boat", 40, 10),
+    ("tub", 10, 20),
+    ("blinker", 60, 10),
+    ("toad", 70, 15),
+    ("beacon", 70, 25),
+    ("pulsar", 55, 40),
+    ("pentadecathlon", 85, 10),
+    ("glider", 10, 70),
+    ("glider", 20, 80),
+    ("lwss", 35, 70),
+    ("gosper-glider-gun", 55, 80),
+    ("r-pentomino", 30, 40),
+    ("acorn", 20, 50),
+    ("diehard", 60, 50),
+    ("mwss", 20, 60),
+    ("hwss", 30, 60),
+    ("block", 50, 30),
+    ("beehive", 60, 30),
+    ("beacon", 40, 20),
+    ("toad", 50, 20),
+];
 
-// Function to create R-pentomino (creates a lot of activity)
-fn create_r_pentomino(grid: &mut Vec<Vec<bool>>, x: usize, y: usize) {
-    if x + 2 < GRID_WIDTH as usize && y + 2 < GRID_HEIGHT as usize {
-        grid[y][x+1] = true;
-        grid[y][x+2] = true;
-        grid[y+1][x] = true;
-        grid[y+1][x+1] = true;
-        grid[y+2][x+1] = true;
-    }
-}
+// Rules offered by the G key: Brian's Brain, Star Wars, Bosco's Rule (Larger than Life)
+// and a hexagonal Life variant
+const GENERATIONS_PRESETS: [&str; 4] = ["/2/3", "345/2/4", "R5,C2,M1,S34-58,B34-45,NM", "B2/S34H"];
 
-// New pattern: Acorn (sparks long evolution)
-fn create_acorn(grid: &mut Vec<Vec<bool>>, x: usize, y: usize) {
-    let pts = [(1,0),(3,0),(0,1),(1,1),(4,1),(5,1),(6,1)];
-    for &(dx,dy) in &pts {
-        let nx = x + dx;
-        let ny = y + dy;
-        if nx < GRID_WIDTH as usize && ny < GRID_HEIGHT as usize {
-            grid[ny][nx] = true;
-        }
-    }
+// Engine driving the simulation
+enum Backend {
+    Dense,               // Toroidal board the size of the grid, stepped by update_grid
+    HashLife(HashLife),  // Quadtree engine on an unbounded plane
+    Sparse(SparseBoard), // Unbounded board stored as 64x64 chunks
+    Generations(Box<GenerationsBoard>), // Toroidal multi-state board with decaying cells
+    Automaton(AutomatonBoard), // Toroidal board of a non-Life automaton such as Wireworld, picked at launch
+    Ants(AntBoard),            // Langton's ants or turmites walking a toroidal board of colours
+    SpaceTime(SpaceTime),      // 1D automaton drawn as a space-time diagram, one row per generation
+    Lenia(Lenia),              // Continuous LENIA_SIZE x LENIA_SIZE field, drawn at its own resolution
+    Margolus(MargolusRule),    // Block automaton stepping the dense grid in alternating 2x2 blocks
 }
 
-// New pattern: Diehard (small pattern with long lifespan)
-fn create_diehard(grid: &mut Vec<Vec<bool>>, x: usize, y: usize) {
-    let pts = [(0,0),(1,0),(1,1),(5,1),(6,1),(7,1),(6,2)];
-    for &(dx,dy) in &pts {
-        let nx = x + dx;
-        let ny = y + dy;
-        if nx < GRID_WIDTH as usize && ny < GRID_HEIGHT as usize {
-            grid[ny][nx] = true;
-        }
-    }
+// What happens when the board settles into a cycle, picked with --on-cycle
+#[derive(Clone, Copy, PartialEq)]
+enum CycleAction {
+    Report, // Only show the cycle in the HUD
+    Pause,  // Pause the simulation
+    Reset,  // Start over from a new soup
 }
 
 // Type of cell to track its origin
@@ -309,6 +184,8 @@ enum CellType {
     Spaceship,  // Gliders, LWSS, etc.
     Custom,     // Custom patterns (Gosper gun, etc.)
     Generated,  // Cells that emerge during simulation
+    Decaying(u8, u8), // Refractory state and state count under a Generations rule
+    State(Color), // Non-dead state of another automaton, in that automaton's colour
     Dead        // Dead cells
 }
 
@@ -320,165 +197,149 @@ fn get_color(cell_type: CellType) -> Color {
         CellType::Spaceship => SPACESHIP_COLOR,
         CellType::Custom => CUSTOM_COLOR,
         CellType::Generated => GENERATED_COLOR,
+        // Later refractory states fade towards the background
+        CellType::Decaying(state, states) => {
+            DECAY_COLOR.lerp(BACKGROUND_COLOR, (state - 2) as f32 / (states - 2) as f32)
+        },
+        CellType::State(color) => color,
         CellType::Dead => BACKGROUND_COLOR,
     }
 }
 
-// Initialize the grid with various life forms
-fn initialize_grid() -> (Vec<Vec<bool>>, Vec<Vec<CellType>>) {
-    let mut grid = vec![vec![false; GRID_WIDTH as usize]; GRID_HEIGHT as usize];
-    let mut cell_types = vec![vec![CellType::Dead; GRID_WIDTH as usize]; GRID_HEIGHT as usize];
-    
-    // No longer need this with mark_region function
-    // Let's keep our grid initialized
-    
-    // Create still life forms (in the top-left quadrant)
-    create_block(&mut grid, 10, 10);
-    mark_region(&mut grid, &mut cell_types, 10, 10, 2, 2, CellType::StillLife);
-    
-    create_beehive(&mut grid, 20, 15);
-    mark_region(&mut grid, &mut cell_types, 20, 15, 4, 3, CellType::StillLife);
-    
-    create_loaf(&mut grid, 30, 10);
-    mark_region(&mut grid, &mut cell_types, 30, 10, 4, 4, CellType::StillLife);
-    
-    create_boat(&mut grid, 40, 10);
-    mark_region(&mut grid, &mut cell_types, 40, 10, 3, 3, CellType::StillLife);
-    
-    create_tub(&mut grid, 10, 20);
-    mark_region(&mut grid, &mut cell_types, 10, 20, 3, 3, CellType::StillLife);
-    
-    // Create oscillators (in the top-right quadrant)
-    create_blinker(&mut grid, 60, 10);
-    mark_region(&mut grid, &mut cell_types, 60, 10, 3, 1, CellType::Oscillator);
-    
-    create_toad(&mut grid, 70, 15);
-    mark_region(&mut grid, &mut cell_types, 70, 15, 4, 2, CellType::Oscillator);
-    
-    create_beacon(&mut grid, 70, 25);
-    mark_region(&mut grid, &mut cell_types, 70, 25, 4, 4, CellType::Oscillator);
-    
-    create_pulsar(&mut grid, 55, 40);
-    mark_region(&mut grid, &mut cell_types, 55, 40, 13, 13, CellType::Oscillator);
-    
-    create_pentadecathlon(&mut grid, 85, 10);
-    mark_region(&mut grid, &mut cell_types, 85, 10, 3, 10, CellType::Oscillator);
-    
-    // Create spaceships (in the bottom-left quadrant)
-    create_glider(&mut grid, 10, 70);
-    mark_region(&mut grid, &mut cell_types, 10, 70, 3, 3, CellType::Spaceship);
-    
-    create_glider(&mut grid, 20, 80);
-    mark_region(&mut grid, &mut cell_types, 20, 80, 3, 3, CellType::Spaceship);
-    
-    create_lwss(&mut grid, 35, 70);
-    mark_region(&mut grid, &mut cell_types, 35, 70, 5, 4, CellType::Spaceship);
-    
-    // Create custom patterns (in the bottom-right quadrant)
-    create_gosper_glider_gun(&mut grid, 55, 80);
-    mark_region(&mut grid, &mut cell_types, 55, 80, 36, 9, CellType::Custom);
-    
-    create_r_pentomino(&mut grid, 30, 40);
-    mark_region(&mut grid, &mut cell_types, 30, 40, 3, 3, CellType::Custom);
-
-    // Add cooler patterns
-    create_acorn(&mut grid, 20, 50);
-    mark_region(&mut grid, &mut cell_types, 20, 50, 7, 2, CellType::Custom);
-    create_diehard(&mut grid, 60, 50);
-    mark_region(&mut grid, &mut cell_types, 60, 50, 8, 3, CellType::Custom);
-    
-    // Add more spaceships
-    create_mwss(&mut grid, 20, 60);
-    mark_region(&mut grid, &mut cell_types, 20, 60, 6, 5, CellType::Spaceship);
-    create_hwss(&mut grid, 30, 60);
-    mark_region(&mut grid, &mut cell_types, 30, 60, 7, 5, CellType::Spaceship);
-    
-    // Add more still lifes in different areas
-    create_block(&mut grid, 50, 30);
-    mark_region(&mut grid, &mut cell_types, 50, 30, 2, 2, CellType::StillLife);
-    create_beehive(&mut grid, 60, 30);
-    mark_region(&mut grid, &mut cell_types, 60, 30, 4, 3, CellType::StillLife);
-    
-    // Add more oscillators
-    create_beacon(&mut grid, 40, 20);
-    mark_region(&mut grid, &mut cell_types, 40, 20, 4, 4, CellType::Oscillator);
-    create_toad(&mut grid, 50, 20);
-    mark_region(&mut grid, &mut cell_types, 50, 20, 4, 2, CellType::Oscillator);
-    
-    // Add random cells for more dynamism
-    let mut rng = thread_rng();
-    for y in 0..GRID_HEIGHT as usize {
-        for x in 0..GRID_WIDTH as usize {
-           if rng.r#gen::<f64>() < 0.02  { 
-                // 2% chance of a cell being alive
-                grid[y][x] = true;
-                cell_types[y][x] = CellType::Generated;
-            }
-        }
+// A pattern on the starting board: its cells, top-left cell and cell type
+type Stamp = (Pattern, (usize, usize), CellType);
+
+// Initialize a grid with various life forms and random soups. Patterns wrap around
+// the edges of a toroidal board and are cut at the edges of a view of the plane
+fn initialize_grid(size: (usize, usize), stamps: &[Stamp], soups: &[Soup], wraps: bool) -> (Vec<Vec<bool>>, Vec<Vec<CellType>>) {
+    let (width, height) = size;
+    let mut data = (vec![vec![false; width]; height], vec![vec![CellType::Dead; width]; height]);
+    for (pattern, (x, y), cell_type) in stamps {
+        stamp(&mut data, pattern, *x, *y, *cell_type, wraps);
+    }
+
+    // Add a random soup for more dynamism; the same seed always gives the same soup
+    for (x, y) in soups.iter().flat_map(Soup::cells) {
+        data.0[y][x] = true;
+        data.1[y][x] = CellType::Generated;
     }
+    data
+}
 
-    (grid, cell_types)
+// The usual starting board's patterns, taken from the library
+fn default_stamps(library: &Library) -> Vec<Stamp> {
+    INITIAL_PATTERNS
+        .iter()
+        .filter_map(|&(name, x, y)| library.get(name).map(|entry| (entry.pattern.clone(), (x, y), category_type(entry.category))))
+        .collect()
 }
 
-// Helper function to mark a region with a cell type
-fn mark_region(grid: &mut Vec<Vec<bool>>, cell_types: &mut Vec<Vec<CellType>>, 
-               x: usize, y: usize, width: usize, height: usize, cell_type: CellType) {
-    for j in 0..height {
-        for i in 0..width {
-            let nx = x + i;
-            let ny = y + j;
-            if nx < GRID_WIDTH as usize && ny < GRID_HEIGHT as usize && grid[ny][nx] {
-                cell_types[ny][nx] = cell_type;
+// A scenario's patterns, from the library or their files, oriented and coloured
+// by the category the scenario gives, or else the library's
+fn scenario_stamps(scenario: &Scenario, library: &Library) -> Result<Vec<Stamp>, String> {
+    let mut stamps = Vec::new();
+    for placed in &scenario.patterns {
+        let (pattern, category) = placed.pattern(library)?;
+        stamps.push((pattern, placed.at, category_type(category)));
+    }
+    Ok(stamps)
+}
+
+// The grid and backend a scenario starts with
+type Start = ((Vec<Vec<bool>>, Vec<Vec<CellType>>), Backend);
+
+// Start a scenario, its soups drawn from a seed
+fn start_scenario(scenario: &Scenario, library: &Library, seed: u64) -> Result<Start, String> {
+    let stamps = scenario_stamps(scenario, library)?;
+    let torus = scenario.topology == Topology::Torus;
+    let data = initialize_grid((scenario.width, scenario.height), &stamps, &scenario.soups(seed), torus);
+
+    let life = GenerationsRule::parse("B3/S23").expect("Life is a valid rule");
+    let rule = scenario.rule.as_deref().map(GenerationsRule::parse).transpose()?.filter(|rule| *rule != life);
+    let backend = match (scenario.topology, rule) {
+        (Topology::Torus, None) => Backend::Dense,
+        (Topology::Torus, Some(rule)) => Backend::Generations(Box::new(GenerationsBoard::from_grid(&data.0, rule))),
+        // Patterns run past the view on the plane, so they are set on the board whole
+        (Topology::Plane, None) => {
+            let mut board = SparseBoard::from_grid(&data.0);
+            for (pattern, (x, y), _) in &stamps {
+                for (j, row) in pattern.cells.iter().enumerate() {
+                    for (i, _) in row.iter().enumerate().filter(|(_, state)| **state != 0) {
+                        board.set((x + i) as i64, (y + j) as i64, true);
+                    }
+                }
+            }
+            Backend::Sparse(board)
+        },
+        (Topology::Plane, Some(_)) => return Err("only Life runs on the plane topology".to_string()),
+    };
+    Ok((data, backend))
+}
+
+// Cell type, and so colour, of the patterns of a library category
+fn category_type(category: Category) -> CellType {
+    match category {
+        Category::StillLife => CellType::StillLife,
+        Category::Oscillator => CellType::Oscillator,
+        Category::Spaceship => CellType::Spaceship,
+        Category::Gun | Category::Methuselah | Category::Other => CellType::Custom,
+    }
+}
+
+// Set the live cells of a pattern on the dense grid with its top-left corner
+// at (x, y), wrapping around the edges or cut at them, and mark them with a cell type
+fn stamp(data: &mut (Vec<Vec<bool>>, Vec<Vec<CellType>>), pattern: &Pattern, x: usize, y: usize, cell_type: CellType, wraps: bool) {
+    let (width, height) = grid_size(&data.0);
+    for (j, row) in pattern.cells.iter().enumerate() {
+        for (i, _) in row.iter().enumerate().filter(|(_, state)| **state != 0) {
+            if !wraps && (x + i >= width || y + j >= height) {
+                continue;
             }
+            let (cx, cy) = ((x + i) % width, (y + j) % height);
+            data.0[cy][cx] = true;
+            data.1[cy][cx] = cell_type;
         }
     }
 }
 
-// Update the grid based on Conway's Game of Life rules
-fn update_grid(data: &(Vec<Vec<bool>>, Vec<Vec<CellType>>)) -> (Vec<Vec<bool>>, Vec<Vec<CellType>>) {
+// Columns and rows of a grid
+fn grid_size<T>(grid: &[Vec<T>]) -> (usize, usize) {
+    (grid.first().map_or(0, Vec::len), grid.len())
+}
+
+// Update the grid based on Conway's Game of Life rules. With inherit_colors set,
+// a newborn takes the majority type of its three parents, as in Immigration.
+// A stochastic rule makes births and survivals probabilistic and adds noise
+fn update_grid(data: &(Vec<Vec<bool>>, Vec<Vec<CellType>>), inherit_colors: bool, mut stochastic: Option<&mut StochasticRule>) -> (Vec<Vec<bool>>, Vec<Vec<CellType>>) {
     let (grid, cell_types) = data;
-    let mut new_grid = vec![vec![false; GRID_WIDTH as usize]; GRID_HEIGHT as usize];
-    let mut new_cell_types = vec![vec![CellType::Dead; GRID_WIDTH as usize]; GRID_HEIGHT as usize];
-    
-    for y in 0..GRID_HEIGHT as usize {
-        for x in 0..GRID_WIDTH as usize {
-            let mut live_neighbors = 0;
-            
-            // Count live neighbors (using wrap-around/envolvente approach)
-            for dy in -1..=1 {
-                for dx in -1..=1 {
-                    if dx == 0 && dy == 0 { continue; } // Skip self
-                    
-                    // Calculate neighbor coordinates with wrap-around
-                    let nx = (x as i32 + dx).rem_euclid(GRID_WIDTH as i32) as usize;
-                    let ny = (y as i32 + dy).rem_euclid(GRID_HEIGHT as i32) as usize;
-                    
-                    if grid[ny][nx] {
-                        live_neighbors += 1;
-                    }
-                }
+    let (width, height) = grid_size(grid);
+    let mut new_grid = vec![vec![false; width]; height];
+    let mut new_cell_types = vec![vec![CellType::Dead; width]; height];
+
+    for y in 0..height {
+        for x in 0..width {
+            // Step each cell as the Conway automaton (wrap-around/envolvente edges)
+            let mut neighbors = [0u8; 8];
+            for (neighbor, &(dx, dy)) in neighbors.iter_mut().zip(NEIGHBOR_OFFSETS.iter()) {
+                let nx = (x as i32 + dx).rem_euclid(width as i32) as usize;
+                let ny = (y as i32 + dy).rem_euclid(height as i32) as usize;
+                *neighbor = grid[ny][nx] as u8;
             }
-            
-            // Apply Conway's rules
-            if grid[y][x] {
-                // Live cell
-                if live_neighbors < 2 || live_neighbors > 3 {
-                    // Die from underpopulation or overpopulation
-                    new_grid[y][x] = false;
-                    new_cell_types[y][x] = CellType::Dead;
+            let mut alive = Conway.next_state(grid[y][x] as u8, neighbors) == 1;
+            if let Some(rule) = stochastic.as_deref_mut() {
+                alive = rule.apply(grid[y][x], alive);
+            }
+            if alive {
+                new_grid[y][x] = true;
+                // Survivors preserve their type, births are marked as new generated cells
+                new_cell_types[y][x] = if grid[y][x] {
+                    cell_types[y][x]
+                } else if inherit_colors {
+                    let (parents, count) = parent_types(data, x, y);
+                    majority(&parents[..count]).unwrap_or(CellType::Generated)
                 } else {
-                    // Survive - preserve its type
-                    new_grid[y][x] = true;
-                    new_cell_types[y][x] = cell_types[y][x];
-                }
-            } else {
-                // Dead cell
-                if live_neighbors == 3 {
-                    // Reproduction - mark as a new generated cell
-                    new_grid[y][x] = true;
-                    new_cell_types[y][x] = CellType::Generated;
-                }
-                // Otherwise stay dead
+                    CellType::Generated
+                };
             }
         }
     }
@@ -486,69 +347,708 @@ fn update_grid(data: &(Vec<Vec<bool>>, Vec<Vec<CellType>>)) -> (Vec<Vec<bool>>,
     (new_grid, new_cell_types)
 }
 
-// Render the grid onto the framebuffer
-fn render_grid(data: &(Vec<Vec<bool>>, Vec<Vec<CellType>>), framebuffer: &mut Framebuffer) {
-    // Removed full clear to allow incremental rendering via background color per cell
+// Types of the live neighbours of a cell, wrapping around the edges, and how
+// many of the 8 slots are filled
+fn parent_types(data: &(Vec<Vec<bool>>, Vec<Vec<CellType>>), x: usize, y: usize) -> ([CellType; 8], usize) {
+    let (grid, cell_types) = data;
+    let (width, height) = grid_size(grid);
+    let mut parents = [CellType::Dead; 8];
+    let mut count = 0;
+    for &(dx, dy) in NEIGHBOR_OFFSETS.iter() {
+        let nx = (x as i32 + dx).rem_euclid(width as i32) as usize;
+        let ny = (y as i32 + dy).rem_euclid(height as i32) as usize;
+        if grid[ny][nx] {
+            parents[count] = cell_types[ny][nx];
+            count += 1;
+        }
+    }
+    (parents, count)
+}
+
+// Backends whose whole state is the dense grid, so the history can rewind them
+fn rewindable(backend: &Backend) -> bool {
+    matches!(backend, Backend::Dense | Backend::Margolus(_))
+}
+
+// Record the dense grid as the newest generation
+fn record(history: &mut History<(bool, CellType)>, generation: u64, data: &(Vec<Vec<bool>>, Vec<Vec<CellType>>)) {
+    let (grid, cell_types) = data;
+    let cells: Vec<Vec<(bool, CellType)>> = grid
+        .iter()
+        .zip(cell_types)
+        .map(|(row, types)| row.iter().copied().zip(types.iter().copied()).collect())
+        .collect();
+    history.push(generation, &cells);
+}
+
+// Show a recorded generation in the dense grid, with its generation number
+fn rewind(history: &mut History<(bool, CellType)>, position: usize, generation: &mut u64, data: &mut (Vec<Vec<bool>>, Vec<Vec<CellType>>)) {
+    if let Some((past_generation, cells)) = history.seek(position) {
+        let grid = cells.iter().map(|row| row.iter().map(|cell| cell.0).collect()).collect();
+        let cell_types = cells.iter().map(|row| row.iter().map(|cell| cell.1).collect()).collect();
+        *generation = past_generation;
+        *data = (grid, cell_types);
+    }
+}
+
+// Hash of everything that decides a backend's next generation, for the
+// deterministic backends that step one generation at a time
+fn board_hash(backend: &Backend, grid: &[Vec<bool>], generation: u64) -> Option<u64> {
+    let mut hasher = DefaultHasher::new();
+    match backend {
+        Backend::Dense => grid.hash(&mut hasher),
+        // The block offset alternates, so it is part of the state
+        Backend::Margolus(_) => (grid, generation % 2).hash(&mut hasher),
+        Backend::Generations(board) => board.cells().hash(&mut hasher),
+        Backend::Automaton(board) => board.cells().hash(&mut hasher),
+        Backend::SpaceTime(diagram) => diagram.rows().back().hash(&mut hasher),
+        Backend::HashLife(_) | Backend::Sparse(_) | Backend::Ants(_) | Backend::Lenia(_) => return None,
+    }
+    Some(hasher.finish())
+}
+
+// Show the current board to the cycle detector, returning a newly found cycle.
+// Random runs have no cycles to find
+fn watch_cycles(cycles: &mut CycleDetector, backend: &Backend, grid: &[Vec<bool>], generation: u64, random: bool) -> Option<Cycle> {
+    match board_hash(backend, grid, generation) {
+        Some(hash) if !random => cycles.observe(generation, hash),
+        _ => {
+            cycles.clear();
+            None
+        },
+    }
+}
+
+// Hexagonal rules are shown on a hex grid, everything else on squares
+fn backend_tiling(backend: &Backend) -> Tiling {
+    match backend {
+        Backend::Generations(board) if matches!(board.rule().neighborhood, Neighborhood::Hexagonal(_)) => Tiling::Hexagonal,
+        _ => Tiling::Square,
+    }
+}
+
+// Start a backend of the same kind over a new board
+fn reseed_backend(backend: Backend, grid: &[Vec<bool>], seed: u64) -> Backend {
+    match backend {
+        Backend::Dense => Backend::Dense,
+        Backend::Margolus(rule) => Backend::Margolus(rule),
+        Backend::HashLife(_) => Backend::HashLife(HashLife::from_grid(grid, HASHLIFE_MAX_NODES)),
+        Backend::Sparse(_) => Backend::Sparse(SparseBoard::from_grid(grid)),
+        Backend::Generations(board) => Backend::Generations(Box::new(GenerationsBoard::from_grid(grid, board.rule().clone()))),
+        // Other automata start again from their own initial board
+        Backend::Automaton(mut board) => {
+            board.reset(seed);
+            Backend::Automaton(board)
+        },
+        Backend::Ants(mut board) => {
+            board.reset();
+            Backend::Ants(board)
+        },
+        Backend::SpaceTime(mut diagram) => {
+            diagram.reset();
+            Backend::SpaceTime(diagram)
+        },
+        Backend::Lenia(mut field) => {
+            field.reseed(seed);
+            Backend::Lenia(field)
+        },
+    }
+}
+
+// What the camera sees of an unbounded backend through a grid of some size, None for the dense grid
+fn backend_view(backend: &Backend, camera: (i64, i64), size: (usize, usize)) -> Option<Vec<Vec<bool>>> {
+    let (left, top) = camera;
+    let (width, height) = size;
+    match backend {
+        Backend::Dense | Backend::Generations(_) | Backend::Automaton(_) | Backend::Ants(_) | Backend::SpaceTime(_) | Backend::Lenia(_) | Backend::Margolus(_) => None,
+        Backend::HashLife(universe) => Some(universe.window(left, top, width, height)),
+        Backend::Sparse(board) => Some(board.window(left, top, width, height)),
+    }
+}
+
+// Short description of the active backend for the status bar
+fn backend_status(backend: &Backend, step_exponent: u8, camera: (i64, i64), stochastic: Option<&StochasticRule>, soup: &Soup) -> String {
+    let details = match backend {
+        // Noise only applies to the dense grid; its seed is shown so the run can be replayed
+        Backend::Dense => stochastic.map_or(String::new(), |rule| {
+            format!("Noise b={} s={} n={} seed {}", rule.birth, rule.survival, rule.spontaneous, rule.seed())
+        }),
+        Backend::HashLife(universe) => format!("HashLife x2^{} pop {} cam {},{}", step_exponent, universe.population(), camera.0, camera.1),
+        Backend::Sparse(board) => format!("Sparse pop {} chunks {} cam {},{}", board.population(), board.chunk_count(), camera.0, camera.1),
+        Backend::Generations(board) => format!("Rule {}", board.rule()),
+        Backend::Automaton(board) => format!("{} ({} states)", board.automaton().name(), board.automaton().states()),
+        Backend::Ants(board) => format!("Ants {} x{} x2^{}", board.rule_string(), board.ants().len(), step_exponent),
+        Backend::SpaceTime(diagram) => format!("1D rule {}", diagram.rule()),
+        Backend::Lenia(field) => format!("Lenia {} t={:.1} mass {:.0} seed {}", field.params(), field.time(), field.mass(), field.seed()),
+        Backend::Margolus(rule) => format!("Margolus {}", rule.name()),
+    };
+    // The Life boards start from the soup, so its seed is shown for sharing the start
+    match backend {
+        Backend::Dense | Backend::HashLife(_) | Backend::Sparse(_) | Backend::Generations(_) | Backend::Margolus(_) if details.is_empty() => {
+            format!("Seed {} {}", soup.seed, soup.symmetry.name())
+        },
+        Backend::Dense | Backend::HashLife(_) | Backend::Sparse(_) | Backend::Generations(_) | Backend::Margolus(_) => {
+            format!("Seed {} {} | {}", soup.seed, soup.symmetry.name(), details)
+        },
+        _ => details,
+    }
+}
 
+// Pull a view of an unbounded backend into the dense grid used for rendering.
+// Survivors keep their type and newborns become generated cells, as in update_grid
+fn sync_from_view(new_grid: Vec<Vec<bool>>, data: &(Vec<Vec<bool>>, Vec<Vec<CellType>>)) -> (Vec<Vec<bool>>, Vec<Vec<CellType>>) {
     let (grid, cell_types) = data;
+    let (width, height) = grid_size(&new_grid);
+    let mut new_cell_types = vec![vec![CellType::Dead; width]; height];
 
-    // Calculate cell size based on framebuffer dimensions and grid size
-    // Leave space for UI and borders
-    let fb_width = framebuffer.width() as u32;
-    let fb_height = framebuffer.height() as u32;
+    for y in 0..height {
+        for x in 0..width {
+            if new_grid[y][x] {
+                new_cell_types[y][x] = if grid[y][x] { cell_types[y][x] } else { CellType::Generated };
+            }
+        }
+    }
+
+    (new_grid, new_cell_types)
+}
 
-    // Reserve 40px for the UI at the bottom
+// Pull a Generations board into the dense grid: state 1 cells are alive and keep
+// their type if they were alive before, refractory cells are shown as decaying
+fn sync_from_generations(board: &GenerationsBoard, data: &(Vec<Vec<bool>>, Vec<Vec<CellType>>)) -> (Vec<Vec<bool>>, Vec<Vec<CellType>>) {
+    let (grid, cell_types) = data;
+    let states = board.rule().states;
+    let (width, height) = grid_size(board.cells());
+    let mut new_grid = vec![vec![false; width]; height];
+    let mut new_cell_types = vec![vec![CellType::Dead; width]; height];
+
+    for (y, row) in board.cells().iter().enumerate() {
+        for (x, &state) in row.iter().enumerate() {
+            match state {
+                0 => {},
+                1 => {
+                    new_grid[y][x] = true;
+                    new_cell_types[y][x] = if grid[y][x] { cell_types[y][x] } else { CellType::Generated };
+                },
+                _ => new_cell_types[y][x] = CellType::Decaying(state, states),
+            }
+        }
+    }
+
+    (new_grid, new_cell_types)
+}
+
+// Where a grid of some size sits on a framebuffer for a tiling, leaving 40px for the UI at the bottom
+fn grid_layout(tiling: Tiling, size: (usize, usize), fb_width: u32, fb_height: u32) -> Layout {
     let available_height = if fb_height > 40 { fb_height - 40 } else { fb_height };
+    Layout::new(tiling, size.0, size.1, fb_width, available_height, BORDER_SIZE)
+}
 
-    // Calculate dynamic cell size based on available space
-    let horizontal_cell_size = (fb_width - (BORDER_SIZE * 2)) / GRID_WIDTH;
-    let vertical_cell_size = (available_height - (BORDER_SIZE * 2)) / GRID_HEIGHT;
+// Pull another automaton's board into the dense grid: every non-empty state is
+// shown in the automaton's own colour
+fn sync_from_automaton(board: &AutomatonBoard) -> (Vec<Vec<bool>>, Vec<Vec<CellType>>) {
+    let automaton = board.automaton();
+    let (width, height) = grid_size(board.cells());
+    let mut new_grid = vec![vec![false; width]; height];
+    let mut new_cell_types = vec![vec![CellType::Dead; width]; height];
 
-    // Use the smaller of the two to maintain square cells
-    let cell_size = horizontal_cell_size.min(vertical_cell_size).max(1); // Ensure at least 1px
+    for (y, row) in board.cells().iter().enumerate() {
+        for (x, &state) in row.iter().enumerate() {
+            if state != 0 {
+                new_grid[y][x] = true;
+                new_cell_types[y][x] = CellType::State(automaton.color(state));
+            }
+        }
+    }
 
-    // Calculate border to center the grid
-    let horizontal_border = (fb_width - (GRID_WIDTH * cell_size)) / 2;
-    let vertical_border = (available_height - (GRID_HEIGHT * cell_size)) / 2;
+    (new_grid, new_cell_types)
+}
 
-    // Draw each cell as either background or its type color
-    for y in 0..GRID_HEIGHT as usize {
-        for x in 0..GRID_WIDTH as usize {
-            let color = if grid[y][x] {
-                get_color(cell_types[y][x])
-            } else {
-                BACKGROUND_COLOR
-            };
-            framebuffer.set_current_color(color);
+// Pull an ant board into the dense grid: every colour but the first is painted
+fn sync_from_ants(board: &AntBoard) -> (Vec<Vec<bool>>, Vec<Vec<CellType>>) {
+    let (width, height) = grid_size(board.cells());
+    let mut new_grid = vec![vec![false; width]; height];
+    let mut new_cell_types = vec![vec![CellType::Dead; width]; height];
+
+    for (y, row) in board.cells().iter().enumerate() {
+        for (x, &color) in row.iter().enumerate() {
+            if color != 0 {
+                new_grid[y][x] = true;
+                new_cell_types[y][x] = CellType::State(ANT_TRAIL_COLORS[(color as usize - 1) % ANT_TRAIL_COLORS.len()]);
+            }
+        }
+    }
+
+    (new_grid, new_cell_types)
+}
+
+// Pull a space-time diagram into the dense grid: row y is the y-th oldest
+// generation kept, so the newest one is at the bottom once the diagram fills up
+fn sync_from_space_time(diagram: &SpaceTime) -> (Vec<Vec<bool>>, Vec<Vec<CellType>>) {
+    let (width, height) = diagram.size();
+    let mut new_grid = vec![vec![false; width]; height];
+    let mut new_cell_types = vec![vec![CellType::Dead; width]; height];
+
+    for (y, row) in diagram.rows().iter().enumerate() {
+        for (x, &alive) in row.iter().enumerate() {
+            if alive {
+                new_grid[y][x] = true;
+                new_cell_types[y][x] = CellType::Generated;
+            }
+        }
+    }
+
+    (new_grid, new_cell_types)
+}
+
+// Draw each ant as an arrow pointing where it is heading, over the rendered grid
+fn draw_ants(board: &AntBoard, framebuffer: &mut Framebuffer, tiling: Tiling) {
+    let layout = grid_layout(tiling, grid_size(board.cells()), framebuffer.width() as u32, framebuffer.height() as u32);
+    let reach = (layout.size * 0.45).max(1.0);
+    framebuffer.set_current_color(ANT_COLOR);
+
+    for ant in board.ants() {
+        let (cx, cy) = layout.cell_centre(ant.x, ant.y);
+        let (dx, dy) = ant.direction();
+        let (dx, dy) = (dx as f32 * reach, dy as f32 * reach);
+        let tip = Vector2::new(cx + dx, cy + dy);
+
+        // Shaft from the back of the cell to the tip, plus two barbs
+        line::line(framebuffer, Vector2::new(cx - dx, cy - dy), tip);
+        line::line(framebuffer, tip, Vector2::new(cx - dy * 0.6, cy + dx * 0.6));
+        line::line(framebuffer, tip, Vector2::new(cx + dy * 0.6, cy - dx * 0.6));
+    }
+}
+
+// Map a Lenia value in [0, 1] through the colour map
+fn lenia_color(value: f32) -> Color {
+    let position = value.clamp(0.0, 1.0) * (LENIA_COLOR_MAP.len() - 1) as f32;
+    let index = (position as usize).min(LENIA_COLOR_MAP.len() - 2);
+    LENIA_COLOR_MAP[index].lerp(LENIA_COLOR_MAP[index + 1], position - index as f32)
+}
+
+// Where the Lenia field sits on the framebuffer; always square cells
+fn field_layout(field: &Lenia, fb_width: u32, fb_height: u32) -> Layout {
+    let available_height = if fb_height > 40 { fb_height - 40 } else { fb_height };
+    Layout::new(Tiling::Square, field.size(), field.size(), fb_width, available_height, BORDER_SIZE)
+}
+
+// Render a Lenia field cell by cell through the colour map
+fn render_field(field: &Lenia, framebuffer: &mut Framebuffer) {
+    let layout = field_layout(field, framebuffer.width() as u32, framebuffer.height() as u32);
+    let (left, top, cell_size) = (layout.left as u32, layout.top as u32, layout.size as u32);
+
+    for y in 0..field.size() {
+        for x in 0..field.size() {
+            framebuffer.set_current_color(lenia_color(field.value(x, y)));
             for dy in 0..cell_size {
                 for dx in 0..cell_size {
-                    let px = horizontal_border + (x as u32 * cell_size) + dx;
-                    let py = vertical_border + (y as u32 * cell_size) + dy;
-                    framebuffer.set_pixel(px, py);
+                    framebuffer.set_pixel(left + x as u32 * cell_size + dx, top + y as u32 * cell_size + dy);
+                }
+            }
+        }
+    }
+}
+
+// Render whatever the active backend shows: the Lenia field, or the grid plus any ants on it
+fn render(backend: &Backend, data: &(Vec<Vec<bool>>, Vec<Vec<CellType>>), framebuffer: &mut Framebuffer, tiling: Tiling) {
+    match backend {
+        Backend::Lenia(field) => render_field(field, framebuffer),
+        Backend::Ants(board) => {
+            render_grid(data, framebuffer, tiling);
+            draw_ants(board, framebuffer, tiling);
+        },
+        _ => render_grid(data, framebuffer, tiling),
+    }
+}
+
+// Render the grid onto the framebuffer
+fn render_grid(data: &(Vec<Vec<bool>>, Vec<Vec<CellType>>), framebuffer: &mut Framebuffer, tiling: Tiling) {
+    // Removed full clear to allow incremental rendering via background color per cell
+
+    let (grid, cell_types) = data;
+    let (width, height) = grid_size(grid);
+    let layout = grid_layout(tiling, (width, height), framebuffer.width() as u32, framebuffer.height() as u32);
+    let cell_color = |x: usize, y: usize| match cell_types[y][x] {
+        CellType::Decaying(..) => get_color(cell_types[y][x]),
+        cell_type if grid[y][x] => get_color(cell_type),
+        _ => BACKGROUND_COLOR,
+    };
+
+    let horizontal_border = layout.left as u32;
+    let vertical_border = layout.top as u32;
+    let (board_width, board_height) = layout.pixel_size();
+    let (board_width, board_height) = (board_width.ceil() as u32, board_height.ceil() as u32);
+
+    if tiling == Tiling::Square {
+        // Draw each cell as either background or its type color
+        let cell_size = layout.size as u32;
+        for y in 0..height {
+            for x in 0..width {
+                framebuffer.set_current_color(cell_color(x, y));
+                for dy in 0..cell_size {
+                    for dx in 0..cell_size {
+                        let px = horizontal_border + (x as u32 * cell_size) + dx;
+                        let py = vertical_border + (y as u32 * cell_size) + dy;
+                        framebuffer.set_pixel(px, py);
+                    }
                 }
             }
         }
+    } else {
+        // Colour every pixel by the cell picked under its centre, so hexagons and
+        // triangles come out filled and match mouse picking exactly
+        for py in vertical_border..vertical_border + board_height {
+            for px in horizontal_border..horizontal_border + board_width {
+                let color = match layout.pick(px as f32 + 0.5, py as f32 + 0.5) {
+                    Some((x, y)) => cell_color(x, y),
+                    None => BACKGROUND_COLOR,
+                };
+                framebuffer.set_current_color(color);
+                framebuffer.set_pixel(px, py);
+            }
+        }
     }
 
     // Draw a frame around the grid
     framebuffer.set_current_color(Color::WHITE);
-    for x in 0..GRID_WIDTH * cell_size + 2 {
+    for x in 0..board_width + 2 {
         // Top border
         framebuffer.set_pixel(horizontal_border - 1 + x, vertical_border - 1);
         // Bottom border
-        framebuffer.set_pixel(horizontal_border - 1 + x, vertical_border + GRID_HEIGHT * cell_size);
+        framebuffer.set_pixel(horizontal_border - 1 + x, vertical_border + board_height);
     }
     
-    for y in 0..GRID_HEIGHT * cell_size + 2 {
+    for y in 0..board_height + 2 {
         // Left border
         framebuffer.set_pixel(horizontal_border - 1, vertical_border - 1 + y);
         // Right border
-        framebuffer.set_pixel(horizontal_border + GRID_WIDTH * cell_size, vertical_border - 1 + y);
+        framebuffer.set_pixel(horizontal_border + board_width, vertical_border - 1 + y);
+    }
+}
+
+// Set or clear one cell of the visible grid, in the dense grid and in the active backend.
+// Drawn cells count as custom patterns
+fn edit_cell(backend: &mut Backend, camera: (i64, i64), data: &mut (Vec<Vec<bool>>, Vec<Vec<CellType>>), x: usize, y: usize, alive: bool) {
+    let (grid, cell_types) = data;
+    grid[y][x] = alive;
+    cell_types[y][x] = if alive { CellType::Custom } else { CellType::Dead };
+
+    let (board_x, board_y) = (camera.0 + x as i64, camera.1 + y as i64);
+    match backend {
+        Backend::Dense | Backend::Margolus(_) => {},
+        Backend::HashLife(universe) => universe.set_cell(board_x, board_y, alive),
+        Backend::Sparse(board) => board.set(board_x, board_y, alive),
+        Backend::Generations(board) => board.set_cell(x, y, alive as u8),
+        Backend::Automaton(board) => {
+            let automaton = board.automaton();
+            let state = if alive { automaton.drawing_state() } else { 0 };
+            cell_types[y][x] = if alive { CellType::State(automaton.color(state)) } else { CellType::Dead };
+            board.set_cell(x, y, state);
+        },
+        Backend::Ants(board) => {
+            board.set_cell(x, y, alive as u8);
+            cell_types[y][x] = if alive { CellType::State(ANT_TRAIL_COLORS[0]) } else { CellType::Dead };
+        },
+        // Only the newest generation feeds the future, so edits land there whatever row is clicked
+        Backend::SpaceTime(diagram) => {
+            diagram.set_cell(x, alive);
+            *data = sync_from_space_time(diagram);
+        },
+        // Lenia is edited on its own field in the main loop
+        Backend::Lenia(_) => {},
+    }
+}
+
+// Set one cell of the visible grid to a state of the active automaton, clamped
+// to the states it has. Two-state boards only tell dead from alive
+fn set_state(backend: &mut Backend, camera: (i64, i64), data: &mut (Vec<Vec<bool>>, Vec<Vec<CellType>>), x: usize, y: usize, state: u8) {
+    match backend {
+        Backend::Generations(board) => {
+            let states = board.rule().states;
+            let state = state.min(states - 1);
+            board.set_cell(x, y, state);
+            data.0[y][x] = state == 1;
+            data.1[y][x] = match state {
+                0 => CellType::Dead,
+                1 => CellType::Custom,
+                _ => CellType::Decaying(state, states),
+            };
+        },
+        Backend::Automaton(board) => {
+            let state = state.min(board.automaton().states() - 1);
+            board.set_cell(x, y, state);
+            data.0[y][x] = state != 0;
+            data.1[y][x] = if state != 0 { CellType::State(board.automaton().color(state)) } else { CellType::Dead };
+        },
+        Backend::Ants(board) => {
+            let color = state.min(board.colors() - 1);
+            board.set_cell(x, y, color);
+            data.0[y][x] = color != 0;
+            data.1[y][x] = if color != 0 { CellType::State(ANT_TRAIL_COLORS[(color as usize - 1) % ANT_TRAIL_COLORS.len()]) } else { CellType::Dead };
+        },
+        _ => edit_cell(backend, camera, data, x, y, state != 0),
+    }
+}
+
+// Stamp a pattern with its top-left corner on a cell of the visible grid,
+// dead cells included. It wraps around the toroidal boards and runs past the
+// view on the unbounded ones
+fn place_pattern(backend: &mut Backend, camera: (i64, i64), data: &mut (Vec<Vec<bool>>, Vec<Vec<CellType>>), pattern: &Pattern, left: usize, top: usize) {
+    let (width, height) = grid_size(&data.0);
+    for (j, row) in pattern.cells.iter().enumerate() {
+        for (i, &state) in row.iter().enumerate() {
+            let (x, y) = (left + i, top + j);
+            match backend {
+                Backend::HashLife(universe) if x >= width || y >= height => {
+                    universe.set_cell(camera.0 + x as i64, camera.1 + y as i64, state != 0);
+                },
+                Backend::Sparse(board) if x >= width || y >= height => {
+                    board.set(camera.0 + x as i64, camera.1 + y as i64, state != 0);
+                },
+                _ => set_state(backend, camera, data, x % width, y % height, state),
+            }
+        }
+    }
+}
+
+// States of the visible board, for saving it as a pattern
+fn board_states(backend: &Backend, data: &(Vec<Vec<bool>>, Vec<Vec<CellType>>)) -> Vec<Vec<u8>> {
+    match backend {
+        Backend::Generations(board) => board.cells().to_vec(),
+        Backend::Automaton(board) => board.cells().to_vec(),
+        Backend::Ants(board) => board.cells().to_vec(),
+        _ => data.0.iter().map(|row| row.iter().map(|&alive| alive as u8).collect()).collect(),
+    }
+}
+
+// Rule written in saved patterns, when it has a name other programs know
+fn board_rule(backend: &Backend) -> Option<String> {
+    match backend {
+        Backend::Dense | Backend::HashLife(_) | Backend::Sparse(_) => Some("B3/S23".to_string()),
+        Backend::Generations(board) => Some(board.rule().to_string()),
+        Backend::Automaton(board) => Some(board.automaton().name().to_string()),
+        Backend::Ants(_) | Backend::SpaceTime(_) | Backend::Lenia(_) | Backend::Margolus(_) => None,
+    }
+}
+
+// What a pattern file opens as
+enum PatternFile {
+    Pattern(Pattern),    // A pattern to place on the grid
+    Universe(Macrocell), // A Life macrocell file, run on HashLife as it is since it may be far bigger than the grid
+}
+
+// Read a pattern file in whichever format it is in. Macrocell files of plain
+// Life open as a universe when a Life board is running, and are flattened into
+// a pattern otherwise
+fn open_pattern_file(path: &str, life_board: bool) -> Result<PatternFile, String> {
+    let text = std::fs::read_to_string(path).map_err(|err| format!("{}: {}", path, err))?;
+    let opened = match Format::detect(path, &text) {
+        Ok(Format::Macrocell) => Macrocell::parse(&text).and_then(|macrocell| {
+            if life_board && pattern::is_life(macrocell.rule.as_deref()) && macrocell.two_states() {
+                Ok(PatternFile::Universe(macrocell))
+            } else {
+                macrocell.to_pattern().map(PatternFile::Pattern)
+            }
+        }),
+        Ok(format) => format.parse(&text).map(PatternFile::Pattern),
+        Err(err) => Err(err),
+    };
+    opened.map_err(|err| format!("{}: {}", path, err))
+}
+
+// The pattern being placed, as loaded and as it is put down
+struct Placement {
+    pattern: Pattern,
+    oriented: Pattern,
+}
+
+impl Placement {
+    // A pattern that can't be run to the phase is only transformed
+    fn new(pattern: Pattern, orientation: Orientation) -> Self {
+        let oriented = orientation.apply(&pattern).unwrap_or_else(|err| {
+            eprintln!("Placing without the phase: {}", err);
+            orientation.transform.apply(&pattern)
+        });
+        Placement { pattern, oriented }
+    }
+}
+
+// A library pattern to place, announced on the console
+fn choose_library_pattern(entry: &Entry) -> Pattern {
+    let period = entry.period.map_or(String::new(), |period| format!(", period {}", period));
+    println!("Placing {} ({}x{} {}{}): TAB for the next pattern", entry.name, entry.pattern.width(), entry.pattern.height(), entry.category.name(), period);
+    entry.pattern.clone()
+}
+
+// A HashLife board running a macrocell universe, and a camera centring a grid of some size on the universe
+fn open_universe(macrocell: &Macrocell, size: (usize, usize)) -> Result<(Backend, (i64, i64)), String> {
+    let universe = HashLife::from_macrocell(macrocell, HASHLIFE_MAX_NODES)?;
+    Ok((Backend::HashLife(universe), (-(size.0 as i64) / 2, -(size.1 as i64) / 2)))
+}
+
+// Cells of the pattern being placed, with its top-left corner on a cell and
+// wrapping like place_pattern does on the toroidal boards
+fn draw_preview(framebuffer: &mut Framebuffer, pattern: &Pattern, left: usize, top: usize, wraps: bool, layout: &Layout) {
+    let half = (layout.size * 0.3).max(1.0) as i32;
+    framebuffer.set_current_color(PREVIEW_COLOR);
+    for (j, row) in pattern.cells.iter().enumerate() {
+        for (i, _) in row.iter().enumerate().filter(|(_, state)| **state != 0) {
+            if !wraps && (left + i >= layout.columns || top + j >= layout.rows) {
+                continue;
+            }
+            let (cx, cy) = layout.cell_centre((left + i) % layout.columns, (top + j) % layout.rows);
+            for dy in -half..=half {
+                for dx in -half..=half {
+                    framebuffer.set_pixel((cx as i32 + dx).max(0) as u32, (cy as i32 + dy).max(0) as u32);
+                }
+            }
+        }
+    }
+}
+
+// What the status bar shows
+struct Status<'a> {
+    paused: bool,
+    generation: u64,
+    speed: u64,
+    backend: &'a str,
+    timeline: Option<(usize, usize)>, // Shown position and length of the history, when the backend can rewind
+    cycle: Option<Cycle>,             // Cycle the board has settled into, if any
+}
+
+// Horizontal span and row of the history timeline at the bottom of the status bar
+fn timeline_bounds(window_width: u32, window_height: u32) -> (u32, u32, u32) {
+    (10, window_width.saturating_sub(10), window_height.saturating_sub(5))
+}
+
+// History position under a point of the timeline
+fn timeline_position(mouse_x: f32, window_width: u32, window_height: u32, length: usize) -> usize {
+    let (left, right, _) = timeline_bounds(window_width, window_height);
+    let fraction = ((mouse_x - left as f32) / (right - left).max(1) as f32).clamp(0.0, 1.0);
+    (fraction * length.saturating_sub(1) as f32).round() as usize
+}
+
+// Backends running B3/S23, whose grid the object census can classify
+fn census_available(backend: &Backend) -> bool {
+    matches!(backend, Backend::Dense | Backend::HashLife(_) | Backend::Sparse(_))
+}
+
+// Whether the grid is the whole toroidal board rather than a view of the plane
+fn wraps_around(backend: &Backend) -> bool {
+    !matches!(backend, Backend::HashLife(_) | Backend::Sparse(_))
+}
+
+// Recolour objects by what they were seen doing: still, oscillating or moving.
+// Cells of objects that haven't settled keep their type
+fn reclassify(objects: &[SeenObject], cell_types: &mut [Vec<CellType>]) {
+    for object in objects {
+        let cell_type = match object.behaviour {
+            Behaviour::Still => CellType::StillLife,
+            Behaviour::Oscillator(_) => CellType::Oscillator,
+            Behaviour::Spaceship { .. } => CellType::Spaceship,
+            Behaviour::Unsettled => continue,
+        };
+        for &(x, y) in &object.cells {
+            cell_types[y][x] = cell_type;
+        }
+    }
+}
+
+// Tracked spaceships: a trail of past positions fading into the background, an
+// arrow along the heading, and the ship's ID and speed
+fn draw_ships(ships: &ShipTracker, framebuffer: &mut Framebuffer, layout: &Layout) {
+    let cell = |(x, y): (f64, f64)| {
+        let column = (x.round() as i64).rem_euclid(layout.columns as i64) as usize;
+        let row = (y.round() as i64).rem_euclid(layout.rows as i64) as usize;
+        layout.cell_centre(column, row)
+    };
+
+    for ship in ships.ships() {
+        for (age, &point) in ship.trail.iter().enumerate().skip(1) {
+            let fade = age as f32 / ship.trail.len() as f32;
+            framebuffer.set_current_color(SHIP_MARKER_COLOR.lerp(BACKGROUND_COLOR, fade));
+            let (x, y) = cell(point);
+            framebuffer.set_pixel(x as u32, y as u32);
+            framebuffer.set_pixel(x as u32 + 1, y as u32);
+        }
+
+        // Three cells long whatever the speed
+        let (cx, cy) = cell(ship.position());
+        let length = ((ship.dx * ship.dx + ship.dy * ship.dy) as f32).sqrt().max(1.0);
+        let reach = layout.size * 3.0;
+        let (dx, dy) = (ship.dx as f32 / length * reach, ship.dy as f32 / length * reach);
+        let tip = Vector2::new(cx + dx, cy + dy);
+        framebuffer.set_current_color(SHIP_MARKER_COLOR);
+        line::line(framebuffer, Vector2::new(cx, cy), tip);
+        line::line(framebuffer, tip, Vector2::new(cx + dx * 0.6 - dy * 0.3, cy + dy * 0.6 + dx * 0.3));
+        line::line(framebuffer, tip, Vector2::new(cx + dx * 0.6 + dy * 0.3, cy + dy * 0.6 - dx * 0.3));
+        let label = format!("{} {}", ship.id, ship.speed());
+        draw_text(framebuffer, &label, (cx - dx * 0.5) as i32 + 4, (cy - dy * 0.5) as i32 + 4, 1);
+    }
+}
+
+// Object counts in a panel at the top-right corner of the grid, which the next
+// render paints over
+fn draw_census(framebuffer: &mut Framebuffer, counts: &[(String, usize)], layout: &Layout) {
+    let total: usize = counts.iter().map(|(_, count)| count).sum();
+    let mut lines = vec![format!("Objects: {}", total)];
+    for (code, count) in counts.iter().take(CENSUS_LINES) {
+        // Unknown apgcodes can be long, so they are cut short
+        let label = census::name(code).map_or_else(|| code.chars().take(16).collect(), str::to_string);
+        lines.push(format!("{:>4} {}", count, label));
+    }
+    if counts.len() > CENSUS_LINES {
+        lines.push(format!("  +{} more", counts.len() - CENSUS_LINES));
+    }
+
+    let right = (layout.left + layout.pixel_size().0) as u32;
+    let left = right.saturating_sub(panel_size(&lines).0 + 4);
+    draw_panel(framebuffer, &lines, left, layout.top as u32 + 4);
+}
+
+// The key list in a panel at the top-left corner of the grid
+fn draw_help(framebuffer: &mut Framebuffer, layout: &Layout) {
+    let lines: Vec<String> = HELP_LINES.iter().map(|line| line.to_string()).collect();
+    draw_panel(framebuffer, &lines, layout.left as u32 + 4, layout.top as u32 + 4);
+}
+
+// Width and height of a panel of text lines
+fn panel_size(lines: &[String]) -> (u32, u32) {
+    (lines.iter().map(|line| line.len()).max().unwrap_or(0) as u32 * 8 + 8, lines.len() as u32 * 12 + 6)
+}
+
+// Lines of white text on a black box with its top-left corner at (left, top)
+fn draw_panel(framebuffer: &mut Framebuffer, lines: &[String], left: u32, top: u32) {
+    let (width, height) = panel_size(lines);
+    framebuffer.set_current_color(Color::BLACK);
+    for y in top..top + height {
+        for x in left..left + width {
+            framebuffer.set_pixel(x, y);
+        }
+    }
+    framebuffer.set_current_color(Color::WHITE);
+    for (i, line) in lines.iter().enumerate() {
+        draw_text(framebuffer, line, left as i32 + 4, (top + 4 + i as u32 * 12) as i32, 1);
     }
 }
 
 // Draw the UI directly on the framebuffer
-fn draw_ui(framebuffer: &mut Framebuffer, paused: bool, generation: u32, speed: u64, window_width: u32, window_height: u32) {
+fn draw_ui(framebuffer: &mut Framebuffer, status: &Status, window_width: u32, window_height: u32) {
+    let Status { paused, generation, speed, backend: backend_status, timeline, cycle } = *status;
+
+    // Cycle report in the top-left corner, over a strip of background that wipes the previous one
+    framebuffer.set_current_color(BACKGROUND_COLOR);
+    for y in 10..22.min(window_height) {
+        for x in 0..window_width {
+            framebuffer.set_pixel(x, y);
+        }
+    }
+    framebuffer.set_current_color(Color::WHITE);
+    match cycle {
+        Some(Cycle { period: 1, start }) => draw_text(framebuffer, &format!("Stable since gen {}", start), 10, 12, 1),
+        Some(Cycle { period, start }) => draw_text(framebuffer, &format!("Period {} since gen {}", period, start), 10, 12, 1),
+        None => {},
+    }
     // Calculate UI dimensions
     let ui_height = 40;
     let ui_start_y = if window_height > ui_height { window_height - ui_height } else { 0 };
@@ -582,18 +1082,36 @@ fn draw_ui(framebuffer: &mut Framebuffer, paused: bool, generation: u32, speed:
     let gen_pos_x = if compact_ui { 150 } else { window_width as i32 / 3 };
     draw_text(framebuffer, &gen_text, gen_pos_x, (ui_start_y + 10) as i32, 1);
     
-    // Speed indicator
-    let speed_text = format!("Speed: {}ms", speed);
+    // Speed indicator, followed by the backend details
+    let speed_text = if backend_status.is_empty() {
+        format!("Speed: {}ms", speed)
+    } else {
+        format!("Speed: {}ms | {}", speed, backend_status)
+    };
     let speed_pos_x = if compact_ui { 250 } else { 2 * window_width as i32 / 3 };
     draw_text(framebuffer, &speed_text, speed_pos_x, (ui_start_y + 10) as i32, 1);
     
-    // Controls
+    // Controls; F1 lists the rest
     let controls = if compact_ui {
-        "SPACE=Pause | R=Reset | S=Step | UP/DOWN=Speed"
+        "SPACE=Pause | R=Reset | S=Step | F1=Help"
     } else {
-        "Controls: SPACE=Pause | R=Reset | S=Step | UP/DOWN=Speed"
+        "Controls: SPACE=Pause | R=Reset | S=Step | UP/DOWN=Speed | F1=All keys"
     };
     draw_text(framebuffer, controls, 10, (ui_start_y + 25) as i32, 1);
+
+    // History timeline: the recorded span in grey, up to the shown generation in white
+    if let Some((position, length)) = timeline {
+        let (left, right, y) = timeline_bounds(window_width, window_height);
+        let marker = left + ((right - left) as f32 * position as f32 / length.saturating_sub(1).max(1) as f32) as u32;
+        for x in left..right {
+            framebuffer.set_current_color(if x <= marker { Color::WHITE } else { Color::DARKGRAY });
+            framebuffer.set_pixel(x, y);
+        }
+        framebuffer.set_current_color(Color::WHITE);
+        for y in y.saturating_sub(2)..(y + 3).min(window_height) {
+            framebuffer.set_pixel(marker, y);
+        }
+    }
     
     // Only show color legend if we have enough space
     if !compact_ui && window_width >= 600 {
@@ -627,7 +1145,7 @@ fn draw_text(framebuffer: &mut Framebuffer, text: &str, x: i32, y: i32, scale: u
     for c in text.chars() {
         // Simple fixed-width character drawing
         match c {
-            'A'..='Z' | 'a'..='z' | '0'..='9' | ' ' | ':' | '.' | ',' | '=' | '|' | '-' | '_' | '+' | '/' => {
+            'A'..='Z' | 'a'..='z' | '0'..='9' | ' ' | ':' | '.' | ',' | '=' | '|' | '-' | '_' | '+' | '/' | '^' | '[' | ']' | '(' | ')' => {
                 // For each character, draw a simple dot pattern
                 // This is a very basic rendering, just to demonstrate
                 draw_char(framebuffer, c, current_x, y, scale);
@@ -718,6 +1236,11 @@ fn draw_char(framebuffer: &mut Framebuffer, c: char, x: i32, y: i32, scale: u32)
         '_' => vec![(0,6), (1,6), (2,6), (3,6), (4,6)],
         '+' => vec![(2,1), (2,2), (0,3), (1,3), (2,3), (3,3), (4,3), (2,4), (2,5)],
         '/' => vec![(4,0), (4,1), (3,2), (2,3), (1,4), (0,5), (0,6)],
+        '^' => vec![(2,0), (1,1), (3,1), (0,2), (4,2)],
+        '[' => vec![(1,0), (2,0), (3,0), (1,1), (1,2), (1,3), (1,4), (1,5), (1,6), (2,6), (3,6)],
+        ']' => vec![(1,0), (2,0), (3,0), (3,1), (3,2), (3,3), (3,4), (3,5), (1,6), (2,6), (3,6)],
+        '(' => vec![(3,0), (2,1), (1,2), (1,3), (1,4), (2,5), (3,6)],
+        ')' => vec![(1,0), (2,1), (3,2), (3,3), (3,4), (2,5), (1,6)],
         _ => vec![], // Empty for unknown characters
     };
     
@@ -748,8 +1271,31 @@ fn draw_color_square(framebuffer: &mut Framebuffer, color: Color, x: u32, y: i32
 }
 
 fn main() {
-    let mut window_width = (GRID_WIDTH * CELL_SIZE) + (BORDER_SIZE * 2);
-    let mut window_height = (GRID_HEIGHT * CELL_SIZE) + (BORDER_SIZE * 2) + 40; // Extra space for UI
+    let args: Vec<String> = std::env::args().collect();
+
+    // A scenario file given with --scenario describes the starting board instead
+    // of the usual one: grid size, rule, topology, placed patterns and soups
+    let default_size = (GRID_WIDTH as usize, GRID_HEIGHT as usize);
+    let mut scenario = match args.iter().position(|arg| arg == "--scenario").map(|i| args.get(i + 1)) {
+        Some(Some(path)) => match Scenario::load(path, default_size) {
+            Ok(loaded) => Some((path.clone(), loaded)),
+            Err(err) => {
+                eprintln!("Ignoring --scenario: {}", err);
+                None
+            },
+        },
+        Some(None) => {
+            eprintln!("Ignoring --scenario: missing file name");
+            None
+        },
+        None => None,
+    };
+    let size = scenario.as_ref().map_or(default_size, |(_, loaded)| (loaded.width, loaded.height));
+
+    // Larger grids get smaller cells, so the window stays about the same size
+    let cell_size = (GRID_WIDTH.max(GRID_HEIGHT) * CELL_SIZE / size.0.max(size.1) as u32).clamp(1, CELL_SIZE);
+    let mut window_width = (size.0 as u32 * cell_size) + (BORDER_SIZE * 2);
+    let mut window_height = (size.1 as u32 * cell_size) + (BORDER_SIZE * 2) + 40; // Extra space for UI
 
     let (mut window, raylib_thread) = raylib::init()
         .size(window_width as i32, window_height as i32)
@@ -761,19 +1307,288 @@ fn main() {
     let mut framebuffer = Framebuffer::new(window_width, window_height, BACKGROUND_COLOR);
     framebuffer.set_background_color(BACKGROUND_COLOR);
     framebuffer.clear();
-    
-    // Initialize the game grid and cell types
-    let mut grid_data = initialize_grid();
+
+    // The random soup: a random seed unless --seed (or the scenario) gives one, 2%
+    // density over the whole grid unless --soup-density and --soup-region say
+    // otherwise, and the symmetry from --symmetry (C1, D2, D4 or D8). A scenario
+    // has soups of its own, drawn from the same seed
+    let mut soup = Soup {
+        seed: rand::random::<u32>() as u64,
+        density: 0.02,
+        region: (0, 0, size.0, size.1),
+        symmetry: Symmetry::C1,
+    };
+    if let Some(seed) = scenario.as_ref().and_then(|(_, loaded)| loaded.seed) {
+        soup.seed = seed;
+    }
+    if let Some(i) = args.iter().position(|arg| arg == "--seed") {
+        match args.get(i + 1).map(|n| n.parse::<u64>()) {
+            Some(Ok(seed)) => soup.seed = seed,
+            _ => eprintln!("Ignoring --seed: expected a number"),
+        }
+    }
+    if let Some(i) = args.iter().position(|arg| arg == "--soup-density") {
+        match args.get(i + 1).map(|n| n.parse::<f64>()) {
+            Some(Ok(density)) if (0.0..=1.0).contains(&density) => soup.density = density,
+            _ => eprintln!("Ignoring --soup-density: expected a number between 0 and 1"),
+        }
+    }
+    if let Some(i) = args.iter().position(|arg| arg == "--soup-region") {
+        match args.get(i + 1).map(|spec| soup::parse_region(spec)) {
+            Some(Ok((left, top, width, height))) if left + width <= size.0 && top + height <= size.1 => {
+                soup.region = (left, top, width, height);
+            },
+            Some(Ok(_)) => eprintln!("Ignoring --soup-region: region does not fit the {}x{} grid", size.0, size.1),
+            Some(Err(err)) => eprintln!("Ignoring --soup-region: {}", err),
+            None => eprintln!("Ignoring --soup-region: missing region"),
+        }
+    }
+    if let Some(i) = args.iter().position(|arg| arg == "--symmetry") {
+        match args.get(i + 1).map(|name| Symmetry::parse(name)) {
+            Some(Ok(symmetry)) => soup.symmetry = symmetry,
+            Some(Err(err)) => eprintln!("Ignoring --symmetry: {}", err),
+            None => eprintln!("Ignoring --symmetry: missing symmetry"),
+        }
+    }
+
+    // Named patterns to build the board from and to place with P: the built-in
+    // ones, plus every pattern file in the directory given with --library
+    let mut library = Library::builtin();
+    if let Some(i) = args.iter().position(|arg| arg == "--library") {
+        match args.get(i + 1).map(|dir| library.load_dir(dir)) {
+            Some(Ok(errors)) => {
+                for err in errors {
+                    eprintln!("Skipping library pattern {}", err);
+                }
+            },
+            Some(Err(err)) => eprintln!("Ignoring --library: {}", err),
+            None => eprintln!("Ignoring --library: missing directory"),
+        }
+    }
+
+    // Initialize the game grid and cell types, and the backend selection; the
+    // unbounded ones are viewed through a camera
+    let mut backend = Backend::Dense;
+    let mut grid_data = match scenario.as_ref().map(|(_, loaded)| start_scenario(loaded, &library, soup.seed)) {
+        Some(Ok((data, scenario_backend))) => {
+            backend = scenario_backend;
+            data
+        },
+        Some(Err(err)) => {
+            eprintln!("Ignoring --scenario: {}", err);
+            scenario = None;
+            initialize_grid(size, &default_stamps(&library), std::slice::from_ref(&soup), true)
+        },
+        None => initialize_grid(size, &default_stamps(&library), std::slice::from_ref(&soup), true),
+    };
     
     // Render the initial state
-    render_grid(&grid_data, &mut framebuffer);
+    let mut tiling = backend_tiling(&backend);
+    render_grid(&grid_data, &mut framebuffer, tiling);
     
     // Simulation control
     let mut paused = false;
     let mut step_mode = false;
     let mut step_requested = false;
     let mut speed = 100; // milliseconds between updates
-    let mut generation = 0;
+    let mut generation: u64 = 0;
+
+    // The automaton is picked at launch with --automaton; Life runs on the usual backends
+    if let Some(i) = args.iter().position(|arg| arg == "--automaton") {
+        match args.get(i + 1).map(|name| automaton::by_name(name)) {
+            Some(Some(automaton)) if automaton.name() != Conway.name() => {
+                let board = AutomatonBoard::new(automaton, size.0, size.1, soup.seed);
+                grid_data = sync_from_automaton(&board);
+                backend = Backend::Automaton(board);
+            },
+            Some(Some(_)) => {},
+            Some(None) => eprintln!("Ignoring --automaton: expected life, wireworld, immigration or quadlife"),
+            None => eprintln!("Ignoring --automaton: missing automaton name"),
+        }
+    }
+
+    // Ants walk instead when --ants gives a turmite rule such as RL; --ant-count sets how many
+    if let Some(i) = args.iter().position(|arg| arg == "--ants") {
+        let ant_count = match args.iter().position(|arg| arg == "--ant-count").map(|j| args.get(j + 1).and_then(|n| n.parse::<usize>().ok())) {
+            Some(Some(count)) if count > 0 => count,
+            Some(_) => {
+                eprintln!("Ignoring --ant-count: expected a positive number");
+                1
+            },
+            None => 1,
+        };
+        match args.get(i + 1).map(|rule| AntBoard::new(rule, ant_count, size.0, size.1)) {
+            Some(Ok(board)) => {
+                grid_data = sync_from_ants(&board);
+                backend = Backend::Ants(board);
+            },
+            Some(Err(err)) => eprintln!("Ignoring --ants: {}", err),
+            None => eprintln!("Ignoring --ants: missing rule string"),
+        }
+    }
+
+    // A 1D rule given with --rule1d, e.g. 30 or R2,T52, is shown as a scrolling space-time diagram
+    if let Some(i) = args.iter().position(|arg| arg == "--rule1d") {
+        match args.get(i + 1).map(|rule| Rule1D::parse(rule)) {
+            Some(Ok(rule)) => {
+                let diagram = SpaceTime::new(rule, size.0, size.1);
+                grid_data = sync_from_space_time(&diagram);
+                backend = Backend::SpaceTime(diagram);
+            },
+            Some(Err(err)) => eprintln!("Ignoring --rule1d: {}", err),
+            None => eprintln!("Ignoring --rule1d: missing rule"),
+        }
+    }
+
+    // A continuous Lenia world given with --lenia: a preset (orbium, geminium) or
+    // parameters like R=13,mu=0.15,sigma=0.015,dt=0.1,b=1
+    if let Some(i) = args.iter().position(|arg| arg == "--lenia") {
+        match args.get(i + 1).map(|spec| LeniaParams::parse(spec)) {
+            Some(Ok(params)) => backend = Backend::Lenia(Lenia::new(params, LENIA_SIZE, soup.seed)),
+            Some(Err(err)) => eprintln!("Ignoring --lenia: {}", err),
+            None => eprintln!("Ignoring --lenia: missing preset or parameters"),
+        }
+    }
+
+    // A block automaton given with --margolus: a preset (critters, bbm, sand) or a
+    // table of 16 block numbers; it runs on the same grid as Life, starting from the soup
+    if let Some(i) = args.iter().position(|arg| arg == "--margolus") {
+        match args.get(i + 1).map(|spec| MargolusRule::parse(spec)) {
+            Some(Ok(rule)) => backend = Backend::Margolus(rule),
+            Some(Err(err)) => eprintln!("Ignoring --margolus: {}", err),
+            None => eprintln!("Ignoring --margolus: missing preset or block table"),
+        }
+    }
+
+    // Patterns are placed turned by --transform (rotate90, flip-h, transpose...) and
+    // run --phase generations first; Z, X, A and Q change this while placing
+    let mut orientation = Orientation::default();
+    if let Some(i) = args.iter().position(|arg| arg == "--transform") {
+        match args.get(i + 1).map(|name| Transform::parse(name)) {
+            Some(Ok(transform)) => orientation.transform = transform,
+            Some(Err(err)) => eprintln!("Ignoring --transform: {}", err),
+            None => eprintln!("Ignoring --transform: missing transform"),
+        }
+    }
+    if let Some(i) = args.iter().position(|arg| arg == "--phase") {
+        match args.get(i + 1).map(|n| n.parse::<u64>()) {
+            Some(Ok(phase)) if phase <= MAX_PHASE => orientation.phase = phase,
+            _ => eprintln!("Ignoring --phase: expected a number of generations up to {}", MAX_PHASE),
+        }
+    }
+
+    // A pattern file given with --pattern (RLE, .cells, Life 1.05/1.06 or macrocell)
+    // can be placed with P and a click, or straight away at the cell given with
+    // --at x,y (again on every reset). A Life macrocell file opens on HashLife
+    // instead. Files dropped on the window are loaded the same way
+    let mut loaded_pattern: Option<Placement> = None;
+    let mut universe_file: Option<Macrocell> = None;
+    if let Some(i) = args.iter().position(|arg| arg == "--pattern") {
+        let life_board = matches!(backend, Backend::Dense);
+        let loaded = args.get(i + 1).ok_or("missing file name".to_string()).and_then(|path| open_pattern_file(path, life_board));
+        match loaded {
+            Ok(PatternFile::Pattern(pattern)) => loaded_pattern = Some(Placement::new(pattern, orientation)),
+            Ok(PatternFile::Universe(macrocell)) => universe_file = Some(macrocell),
+            Err(err) => eprintln!("Ignoring --pattern: {}", err),
+        }
+    }
+    let mut pattern_origin = None;
+    if let Some(i) = args.iter().position(|arg| arg == "--at") {
+        let origin = args.get(i + 1).and_then(|spec| spec.split_once(',')).and_then(|(x, y)| Some((x.trim().parse::<usize>().ok()?, y.trim().parse::<usize>().ok()?)));
+        match origin {
+            Some((x, y)) if x < size.0 && y < size.1 && loaded_pattern.is_some() => pattern_origin = Some((x, y)),
+            Some(_) if loaded_pattern.is_some() => eprintln!("Ignoring --at: the cell is off the {}x{} grid", size.0, size.1),
+            Some(_) => eprintln!("Ignoring --at: no pattern loaded with --pattern"),
+            None => eprintln!("Ignoring --at: expected a cell like 10,20"),
+        }
+    }
+    if let (Some(placement), Some((x, y))) = (&loaded_pattern, pattern_origin) {
+        place_pattern(&mut backend, (0, 0), &mut grid_data, &placement.oriented, x, y);
+    }
+    let mut placing = false; // Whether the loaded pattern follows the mouse, waiting for a click
+    let mut library_choice: Option<usize> = None; // Library entry being placed, if the pattern is one
+    let save_path = match args.iter().position(|arg| arg == "--save").map(|i| args.get(i + 1)) {
+        Some(Some(path)) => path.clone(),
+        Some(None) => {
+            eprintln!("Ignoring --save: missing file name");
+            SAVED_PATTERN_FILE.to_string()
+        },
+        None => SAVED_PATTERN_FILE.to_string(),
+    };
+
+    // Life-only controls (rules and engines) are off for other automata
+    let life_controls = !matches!(backend, Backend::Automaton(_) | Backend::Ants(_) | Backend::SpaceTime(_) | Backend::Lenia(_) | Backend::Margolus(_));
+    // Probabilistic births/survivals and spontaneous births from --noise, e.g.
+    // "b=0.9,s=0.95,n=0.001", with a random seed unless --noise-seed gives one
+    let mut stochastic = None;
+    if let Some(i) = args.iter().position(|arg| arg == "--noise") {
+        let seed = match args.iter().position(|arg| arg == "--noise-seed").map(|j| args.get(j + 1).and_then(|n| n.parse::<u64>().ok())) {
+            Some(Some(seed)) => seed,
+            Some(None) => {
+                eprintln!("Ignoring --noise-seed: expected a number");
+                rand::random()
+            },
+            None => rand::random(),
+        };
+        match args.get(i + 1).map(|spec| StochasticRule::parse(spec, seed)) {
+            Some(Ok(rule)) => stochastic = Some(rule),
+            Some(Err(err)) => eprintln!("Ignoring --noise: {}", err),
+            None => eprintln!("Ignoring --noise: missing probabilities"),
+        }
+    }
+    let mut inherit_colors = false; // Newborns take their parents' majority type instead of Generated
+    let mut step_exponent: u8 = 0; // HashLife advances 2^step_exponent generations per update
+    let mut camera: (i64, i64) = (0, 0); // Board cell shown at the grid's top-left corner
+    if let Some(macrocell) = &universe_file {
+        match open_universe(macrocell, size) {
+            Ok((universe, universe_camera)) => {
+                (backend, camera) = (universe, universe_camera);
+                generation = macrocell.generation;
+                grid_data = sync_from_view(backend_view(&backend, camera, size).unwrap_or_default(), &grid_data);
+            },
+            Err(err) => eprintln!("Ignoring --pattern: {}", err),
+        }
+    }
+
+    // Past generations of the grid, for stepping back and branching new runs
+    let mut history = History::new(HISTORY_LENGTH);
+    record(&mut history, generation, &grid_data);
+
+    // Cycle detection, optionally pausing or starting over once the board repeats
+    // (--on-cycle pause or --on-cycle reset)
+    let mut cycles = CycleDetector::new(CYCLE_WINDOW);
+    let mut cycle_action = CycleAction::Report;
+    if let Some(i) = args.iter().position(|arg| arg == "--on-cycle") {
+        match args.get(i + 1).map(|action| action.as_str()) {
+            Some("pause") => cycle_action = CycleAction::Pause,
+            Some("reset") => cycle_action = CycleAction::Reset,
+            _ => eprintln!("Ignoring --on-cycle: expected pause or reset"),
+        }
+    }
+    let mut reset_request: Option<bool> = None; // A pending reset, and whether it draws a new soup seed
+
+    // Object census of the Life grid, shown with O and saved with E
+    let mut classifier = Classifier::default();
+    let mut show_census = false;
+    let mut show_help = false; // The key list, toggled with F1
+    // Life objects are recoloured as they are seen behaving, and spaceships are
+    // followed with arrows and trails (V hides them)
+    let mut tracker = BehaviourTracker::default();
+    let mut ships = ShipTracker::default();
+    let mut show_ships = true;
+
+    // Generations rules cycled by G; a rule given with --rule on the command line comes first
+    let mut generations_rules = Vec::new();
+    if let Some(i) = args.iter().position(|arg| arg == "--rule") {
+        match args.get(i + 1).map(|rule| GenerationsRule::parse(rule)) {
+            Some(Ok(rule)) => generations_rules.push(rule),
+            Some(Err(err)) => eprintln!("Ignoring --rule: {}", err),
+            None => eprintln!("Ignoring --rule: missing rule string"),
+        }
+    }
+    for preset in GENERATIONS_PRESETS {
+        generations_rules.push(GenerationsRule::parse(preset).expect("preset rules are valid"));
+    }
     
     // Main game loop
     while !window.window_should_close() {
@@ -792,12 +1607,15 @@ fn main() {
             framebuffer.set_background_color(BACKGROUND_COLOR);
             
             // Re-render the current state
-            render_grid(&grid_data, &mut framebuffer);
-            draw_ui(&mut framebuffer, paused, generation, speed, window_width, window_height);
+            render(&backend, &grid_data, &mut framebuffer, tiling);
+            let status = backend_status(&backend, step_exponent, camera, stochastic.as_ref(), &soup);
+            let timeline = rewindable(&backend).then(|| (history.position(), history.len()));
+            draw_ui(&mut framebuffer, &Status { paused, generation, speed, backend: &status, timeline, cycle: cycles.cycle() }, window_width, window_height);
         }
         
         // Check for user input without drawing
         let mut input = window.get_key_pressed();
+        let mut view_dirty = false;
         
         // Handle input
         while let Some(key) = input {
@@ -807,10 +1625,139 @@ fn main() {
                     step_mode = true;
                     step_requested = true;
                 },
-                KeyboardKey::KEY_R => {
-                    // Reset the simulation
-                    grid_data = initialize_grid();
-                    generation = 0;
+                // Reset the simulation, replaying the same soup; N draws a new seed first
+                KeyboardKey::KEY_R => reset_request = Some(false),
+                KeyboardKey::KEY_N => reset_request = Some(true),
+                KeyboardKey::KEY_H if life_controls => {
+                    // Toggle the HashLife backend, seeding it from the visible board
+                    backend = match backend {
+                        Backend::HashLife(_) => Backend::Dense,
+                        _ => Backend::HashLife(HashLife::from_grid(&grid_data.0, HASHLIFE_MAX_NODES)),
+                    };
+                    camera = (0, 0);
+                },
+                KeyboardKey::KEY_U if life_controls => {
+                    // Toggle the unbounded sparse backend, seeding it from the visible board
+                    backend = match backend {
+                        Backend::Sparse(_) => Backend::Dense,
+                        _ => Backend::Sparse(SparseBoard::from_grid(&grid_data.0)),
+                    };
+                    camera = (0, 0);
+                },
+                KeyboardKey::KEY_G if life_controls => {
+                    // Cycle through the Generations rules, then back to plain Life
+                    let next = match &backend {
+                        Backend::Generations(board) => generations_rules.iter().position(|rule| rule == board.rule()).map_or(0, |i| i + 1),
+                        _ => 0,
+                    };
+                    backend = match generations_rules.get(next) {
+                        Some(rule) => Backend::Generations(Box::new(GenerationsBoard::from_grid(&grid_data.0, rule.clone()))),
+                        None => Backend::Dense,
+                    };
+                    camera = (0, 0);
+                    view_dirty = true;
+
+                    let new_tiling = backend_tiling(&backend);
+                    if new_tiling != tiling {
+                        tiling = new_tiling;
+                        framebuffer.clear();
+                    }
+                },
+                KeyboardKey::KEY_C if life_controls => {
+                    // Toggle colour inheritance for births on the Life grid
+                    inherit_colors = !inherit_colors;
+                },
+                KeyboardKey::KEY_F1 => {
+                    show_help = !show_help;
+                    // The panel may reach past what the next render paints over
+                    if !show_help {
+                        framebuffer.clear();
+                    }
+                },
+                KeyboardKey::KEY_O if census_available(&backend) => show_census = !show_census,
+                KeyboardKey::KEY_V if census_available(&backend) => show_ships = !show_ships,
+                KeyboardKey::KEY_E if census_available(&backend) => {
+                    // Save the census of the visible board, apgsearch style
+                    let counts = census::counts(&classifier.census(&grid_data.0, wraps_around(&backend)));
+                    let header = format!("# Census at generation {} of soup {} {}\n", generation, soup.seed, soup.symmetry.name());
+                    match std::fs::write(CENSUS_FILE, header + &census::report(&counts)) {
+                        Ok(()) => println!("Census saved to {}", CENSUS_FILE),
+                        Err(err) => eprintln!("Could not save the census: {}", err),
+                    }
+                },
+                KeyboardKey::KEY_P if !matches!(backend, Backend::Lenia(_)) => {
+                    // Without a pattern file, place the library's patterns
+                    if loaded_pattern.is_none() {
+                        library_choice = Some(0);
+                        loaded_pattern = Some(Placement::new(choose_library_pattern(&library.entries()[0]), orientation));
+                    }
+                    placing = !placing;
+                },
+                KeyboardKey::KEY_TAB if placing => {
+                    // Next pattern of the library
+                    let next = library_choice.map_or(0, |i| (i + 1) % library.entries().len());
+                    library_choice = Some(next);
+                    loaded_pattern = Some(Placement::new(choose_library_pattern(&library.entries()[next]), orientation));
+                },
+                KeyboardKey::KEY_Z | KeyboardKey::KEY_X | KeyboardKey::KEY_A | KeyboardKey::KEY_Q if placing => {
+                    // Z turns the pattern clockwise, X mirrors it left to right, A runs
+                    // it one more generation and Q puts it back as loaded
+                    let next = match key {
+                        KeyboardKey::KEY_Z => Orientation { transform: orientation.transform.then(Transform::Rotate90), ..orientation },
+                        KeyboardKey::KEY_X => Orientation { transform: orientation.transform.then(Transform::FlipHorizontal), ..orientation },
+                        KeyboardKey::KEY_A => Orientation { phase: orientation.phase + 1, ..orientation },
+                        _ => Orientation::default(),
+                    };
+                    if let Some(placement) = loaded_pattern.as_mut() {
+                        match next.apply(&placement.pattern) {
+                            Ok(oriented) => {
+                                orientation = next;
+                                placement.oriented = oriented;
+                                println!("Placing {}", orientation.describe());
+                            },
+                            Err(err) => eprintln!("Could not turn the pattern: {}", err),
+                        }
+                    }
+                },
+                KeyboardKey::KEY_W if !matches!(backend, Backend::Lenia(_)) => {
+                    // Save the visible board, cut down to its live cells; a macrocell
+                    // file of the HashLife board holds the whole universe instead
+                    let mut pattern = Pattern::from_board(&board_states(&backend, &grid_data), board_rule(&backend));
+                    pattern.comments.push(format!("Generation {} of soup {} {}", generation, soup.seed, soup.symmetry.name()));
+                    let saved = match &backend {
+                        Backend::HashLife(universe) if Format::from_extension(&save_path) == Some(Format::Macrocell) => {
+                            let macrocell = Macrocell { rule: pattern.rule, generation, comments: pattern.comments, ..universe.to_macrocell() };
+                            std::fs::write(&save_path, macrocell.write()).map_err(|err| format!("{}: {}", save_path, err))
+                        },
+                        _ => pattern::save(&save_path, &pattern),
+                    };
+                    match saved {
+                        Ok(()) => println!("Board saved to {}", save_path),
+                        Err(err) => eprintln!("Could not save the board: {}", err),
+                    }
+                },
+                KeyboardKey::KEY_T => {
+                    // Cycle square, hexagonal and triangular cells; the old grid is cleared away
+                    tiling = tiling.next();
+                    framebuffer.clear();
+                },
+                KeyboardKey::KEY_I => { camera.1 -= CAMERA_STEP; view_dirty = true; },
+                KeyboardKey::KEY_K => { camera.1 += CAMERA_STEP; view_dirty = true; },
+                KeyboardKey::KEY_J => { camera.0 -= CAMERA_STEP; view_dirty = true; },
+                KeyboardKey::KEY_L => { camera.0 += CAMERA_STEP; view_dirty = true; },
+                KeyboardKey::KEY_LEFT_BRACKET => {
+                    // Smaller HashLife jumps (or fewer ant moves per update)
+                    step_exponent = step_exponent.saturating_sub(1);
+                },
+                KeyboardKey::KEY_RIGHT_BRACKET => {
+                    // Bigger HashLife jumps (or more ant moves per update)
+                    step_exponent = (step_exponent + 1).min(MAX_STEP_EXPONENT);
+                },
+                KeyboardKey::KEY_LEFT | KeyboardKey::KEY_RIGHT if rewindable(&backend) => {
+                    // Step through the history; running again from an older generation branches from it
+                    let position = if key == KeyboardKey::KEY_LEFT { history.position().saturating_sub(1) } else { history.position() + 1 };
+                    rewind(&mut history, position, &mut generation, &mut grid_data);
+                    paused = true;
                 },
                 KeyboardKey::KEY_UP => {
                     // Increase simulation speed
@@ -826,19 +1773,251 @@ fn main() {
             }
             input = window.get_key_pressed();
         }
+
+        if let Some(new_seed) = reset_request.take() {
+            if new_seed {
+                soup.seed = rand::random::<u32>() as u64;
+            }
+            // A scenario is read again, so edits to the file show up; one that no
+            // longer loads leaves the last one in place
+            let started = match &mut scenario {
+                Some((path, current)) => {
+                    match Scenario::load(path, default_size) {
+                        Ok(reloaded) => *current = reloaded,
+                        Err(err) => eprintln!("Could not reload the scenario: {}", err),
+                    }
+                    if !new_seed {
+                        soup.seed = current.seed.unwrap_or(soup.seed);
+                    }
+                    Some(start_scenario(current, &library, soup.seed))
+                },
+                None => None,
+            };
+            let size = grid_size(&grid_data.0);
+            match started {
+                // Other automata keep running over their own boards
+                Some(Ok((data, scenario_backend))) if life_controls => {
+                    grid_data = data;
+                    backend = scenario_backend;
+                    if backend_tiling(&backend) != tiling {
+                        tiling = backend_tiling(&backend);
+                        framebuffer.clear();
+                    }
+                },
+                Some(Ok((data, _))) => {
+                    grid_data = data;
+                    backend = reseed_backend(backend, &grid_data.0, soup.seed);
+                },
+                Some(Err(err)) => {
+                    eprintln!("Could not reload the scenario: {}", err);
+                    grid_data = initialize_grid(size, &default_stamps(&library), std::slice::from_ref(&soup), true);
+                    backend = reseed_backend(backend, &grid_data.0, soup.seed);
+                },
+                None => {
+                    grid_data = initialize_grid(size, &default_stamps(&library), std::slice::from_ref(&soup), true);
+                    backend = reseed_backend(backend, &grid_data.0, soup.seed);
+                },
+            }
+            generation = 0;
+            if let (Some(placement), Some((x, y))) = (&loaded_pattern, pattern_origin) {
+                place_pattern(&mut backend, (0, 0), &mut grid_data, &placement.oriented, x, y);
+            }
+            history.clear();
+            record(&mut history, generation, &grid_data);
+            if let Some(rule) = stochastic.as_mut() {
+                rule.reseed();
+            }
+            camera = (0, 0);
+            // A macrocell universe on HashLife starts over from the file
+            if let (Some(macrocell), Backend::HashLife(_)) = (&universe_file, &backend)
+                && let Ok((universe, universe_camera)) = open_universe(macrocell, grid_size(&grid_data.0))
+            {
+                (backend, camera) = (universe, universe_camera);
+                generation = macrocell.generation;
+            }
+            view_dirty = true;
+        }
         
+        // A pattern file dropped on the window replaces the loaded pattern and is
+        // ready to place, or replaces the Life board if it's a macrocell universe
+        if window.is_file_dropped() {
+            let dropped = window.load_dropped_files();
+            if let Some(path) = dropped.paths().first() {
+                let life_board = matches!(backend, Backend::Dense | Backend::HashLife(_) | Backend::Sparse(_));
+                match open_pattern_file(path, life_board) {
+                    Ok(PatternFile::Pattern(pattern)) => {
+                        println!("Loaded {} ({}x{}): click to place it", pattern.name.as_deref().unwrap_or(path), pattern.width(), pattern.height());
+                        loaded_pattern = Some(Placement::new(pattern, orientation));
+                        library_choice = None;
+                        placing = !matches!(backend, Backend::Lenia(_));
+                    },
+                    Ok(PatternFile::Universe(macrocell)) => match open_universe(&macrocell, grid_size(&grid_data.0)) {
+                        Ok((universe, universe_camera)) => {
+                            println!("Opened {} on HashLife at generation {}", path, macrocell.generation);
+                            (backend, camera) = (universe, universe_camera);
+                            generation = macrocell.generation;
+                            universe_file = Some(macrocell);
+                            history.clear();
+                            view_dirty = true;
+                        },
+                        Err(err) => eprintln!("Could not open {}: {}", path, err),
+                    },
+                    Err(err) => eprintln!("Could not load {}", err),
+                }
+            }
+        }
+
+        // Mouse editing: left button draws cells, right button erases them
+        let drawing = window.is_mouse_button_down(MouseButton::MOUSE_BUTTON_LEFT);
+        let erasing = window.is_mouse_button_down(MouseButton::MOUSE_BUTTON_RIGHT);
+        if placing && let Some(placement) = &loaded_pattern {
+            // A left click stamps the pattern under the mouse, a right click stops placing
+            if window.is_mouse_button_pressed(MouseButton::MOUSE_BUTTON_LEFT) {
+                let mouse = window.get_mouse_position();
+                let layout = grid_layout(tiling, grid_size(&grid_data.0), framebuffer.width() as u32, framebuffer.height() as u32);
+                if let Some((x, y)) = layout.pick(mouse.x, mouse.y) {
+                    place_pattern(&mut backend, camera, &mut grid_data, &placement.oriented, x, y);
+                }
+            } else if window.is_mouse_button_pressed(MouseButton::MOUSE_BUTTON_RIGHT) {
+                placing = false;
+            }
+        } else if drawing || erasing {
+            let mouse = window.get_mouse_position();
+            let (_, _, timeline_y) = timeline_bounds(window_width, window_height);
+            if rewindable(&backend) && drawing && (mouse.y - timeline_y as f32).abs() <= 4.0 {
+                // Scrub the history timeline
+                let position = timeline_position(mouse.x, window_width, window_height, history.len());
+                rewind(&mut history, position, &mut generation, &mut grid_data);
+                paused = true;
+            } else if let Backend::Lenia(field) = &mut backend {
+                // Paint a disc half a kernel wide, full or empty
+                let layout = field_layout(field, framebuffer.width() as u32, framebuffer.height() as u32);
+                if let Some((x, y)) = layout.pick(mouse.x, mouse.y) {
+                    let radius = field.params().radius / 2.0;
+                    field.paint(x, y, radius, if drawing { 1.0 } else { 0.0 });
+                }
+            } else {
+                let layout = grid_layout(tiling, grid_size(&grid_data.0), framebuffer.width() as u32, framebuffer.height() as u32);
+                if let Some((x, y)) = layout.pick(mouse.x, mouse.y) {
+                    edit_cell(&mut backend, camera, &mut grid_data, x, y, drawing);
+                }
+            }
+        }
+        
+        // Edits and rewinds change the board without a step, which restarts the cycle watch
+        watch_cycles(&mut cycles, &backend, &grid_data.0, generation, stochastic.is_some());
+
         // Update the game state if not paused or if step requested
         if (!paused || (step_mode && step_requested)) && !window.window_should_close() {
-            grid_data = update_grid(&grid_data);
-            generation += 1;
+            match &mut backend {
+                Backend::Dense => {
+                    grid_data = update_grid(&grid_data, inherit_colors, stochastic.as_mut());
+                    generation += 1;
+                },
+                // The generation picks the block offset, so the blocks alternate every step
+                Backend::Margolus(rule) => {
+                    grid_data = sync_from_view(rule.step(&grid_data.0, generation), &grid_data);
+                    generation += 1;
+                },
+                Backend::HashLife(universe) => {
+                    universe.step(step_exponent);
+                    generation += 1 << step_exponent;
+                },
+                Backend::Sparse(board) => {
+                    board.step();
+                    generation += 1;
+                },
+                Backend::Generations(board) => {
+                    board.step();
+                    generation += 1;
+                },
+                Backend::Automaton(board) => {
+                    board.step();
+                    generation += 1;
+                },
+                Backend::SpaceTime(diagram) => {
+                    diagram.step();
+                    generation += 1;
+                },
+                Backend::Lenia(field) => {
+                    field.step();
+                    generation += 1;
+                },
+                Backend::Ants(board) => {
+                    // Ants move one cell per step, so 2^k steps per update keep them watchable
+                    for _ in 0..1u64 << step_exponent.min(MAX_ANT_STEP_EXPONENT) {
+                        board.step();
+                    }
+                    generation += 1 << step_exponent.min(MAX_ANT_STEP_EXPONENT);
+                },
+            }
+            // Each new generation goes on the history, dropping any generations after
+            // the one the run continued from; other backends are not recorded
+            if rewindable(&backend) {
+                record(&mut history, generation, &grid_data);
+            } else {
+                history.clear();
+            }
+
+            if let Some(cycle) = watch_cycles(&mut cycles, &backend, &grid_data.0, generation, stochastic.is_some()) {
+                // Logged as well, since an automatic reset wipes it from the HUD
+                println!("Seed {}: period {} since generation {}", soup.seed, cycle.period, cycle.start);
+                match cycle_action {
+                    CycleAction::Report => {},
+                    CycleAction::Pause => paused = true,
+                    CycleAction::Reset => reset_request = Some(true),
+                }
+            }
             step_requested = false; // Reset step flag
+            view_dirty = true;
+        }
+
+        // Refresh the dense grid from the active backend after a step or a pan
+        if view_dirty {
+            if let Backend::Generations(board) = &backend {
+                grid_data = sync_from_generations(board, &grid_data);
+            } else if let Backend::Automaton(board) = &backend {
+                grid_data = sync_from_automaton(board);
+            } else if let Backend::Ants(board) = &backend {
+                grid_data = sync_from_ants(board);
+            } else if let Backend::SpaceTime(diagram) = &backend {
+                grid_data = sync_from_space_time(diagram);
+            } else if let Some(view) = backend_view(&backend, camera, grid_size(&grid_data.0)) {
+                grid_data = sync_from_view(view, &grid_data);
+            }
+            if census_available(&backend) {
+                let objects = tracker.observe(generation, &grid_data.0, wraps_around(&backend));
+                reclassify(&objects, &mut grid_data.1);
+                for event in ships.update(generation, &objects, &grid_data.0, wraps_around(&backend)) {
+                    println!("{}", event);
+                }
+            }
         }
         
         // Render everything to our framebuffer
-        render_grid(&grid_data, &mut framebuffer);
+        render(&backend, &grid_data, &mut framebuffer, tiling);
+        let layout = grid_layout(tiling, grid_size(&grid_data.0), framebuffer.width() as u32, framebuffer.height() as u32);
+        if show_ships && census_available(&backend) {
+            draw_ships(&ships, &mut framebuffer, &layout);
+        }
+        if show_census && census_available(&backend) {
+            let counts = census::counts(&classifier.census(&grid_data.0, wraps_around(&backend)));
+            draw_census(&mut framebuffer, &counts, &layout);
+        }
+        if placing && let Some(placement) = &loaded_pattern {
+            let mouse = window.get_mouse_position();
+            if let Some((x, y)) = layout.pick(mouse.x, mouse.y) {
+                draw_preview(&mut framebuffer, &placement.oriented, x, y, wraps_around(&backend), &layout);
+            }
+        }
+        if show_help {
+            draw_help(&mut framebuffer, &layout);
+        }
         
         // Draw the UI directly on our framebuffer
-        draw_ui(&mut framebuffer, paused, generation, speed, window_width, window_height);
+        let status = backend_status(&backend, step_exponent, camera, stochastic.as_ref(), &soup);
+        let timeline = rewindable(&backend).then(|| (history.position(), history.len()));
+        draw_ui(&mut framebuffer, &Status { paused, generation, speed, backend: &status, timeline, cycle: cycles.cycle() }, window_width, window_height);
         
         // Display the framebuffer with everything on it
         framebuffer.swap_buffers(&mut window, &raylib_thread);
@@ -0,0 +1,144 @@
+// Block cellular automaton on the Margolus neighbourhood: the board is cut into
+// 2x2 blocks, the blocks are shifted by one cell diagonally on every other step,
+// and each block is replaced through a 16-entry table. A block is numbered by its
+// cells: top-left 1, top-right 2, bottom-left 4, bottom-right 8
+#[derive(Clone)]
+pub struct MargolusRule {
+    name: String,
+    table: [u8; 16],
+    wraps: bool, // Whether blocks straddle the edges or the board has hard walls
+}
+
+const TOP_LEFT: u8 = 1;
+const TOP_RIGHT: u8 = 2;
+const BOTTOM_LEFT: u8 = 4;
+const BOTTOM_RIGHT: u8 = 8;
+
+// Half a turn of a block: each cell swaps with the opposite corner
+fn rotate_half_turn(block: u8) -> u8 {
+    (block & TOP_LEFT) << 3 | (block & TOP_RIGHT) << 1 | (block & BOTTOM_LEFT) >> 1 | (block & BOTTOM_RIGHT) >> 3
+}
+
+impl MargolusRule {
+    pub fn new(name: &str, table: [u8; 16], wraps: bool) -> Self {
+        MargolusRule { name: name.to_string(), table, wraps }
+    }
+
+    // Critters: blocks with two live cells stay, the rest are inverted, and
+    // inverted blocks that had three live cells also turn half a turn
+    pub fn critters() -> Self {
+        let mut table = [0; 16];
+        for (block, next) in table.iter_mut().enumerate() {
+            let block = block as u8;
+            *next = match block.count_ones() {
+                2 => block,
+                3 => rotate_half_turn(!block & 15),
+                _ => !block & 15,
+            };
+        }
+        MargolusRule::new("Critters", table, true)
+    }
+
+    // Billiard Ball Machine: a lone ball crosses to the opposite corner, two balls
+    // meeting on a diagonal bounce off along the other one, anything else stays
+    pub fn billiard_balls() -> Self {
+        let mut table: [u8; 16] = std::array::from_fn(|block| block as u8);
+        for ball in [TOP_LEFT, TOP_RIGHT, BOTTOM_LEFT, BOTTOM_RIGHT] {
+            table[ball as usize] = rotate_half_turn(ball);
+        }
+        table[(TOP_LEFT | BOTTOM_RIGHT) as usize] = TOP_RIGHT | BOTTOM_LEFT;
+        table[(TOP_RIGHT | BOTTOM_LEFT) as usize] = TOP_LEFT | BOTTOM_RIGHT;
+        MargolusRule::new("BBM", table, true)
+    }
+
+    // Falling sand: grains drop when the cell below is empty and a grain sitting
+    // on another slides down the side when that whole side is free. The board
+    // does not wrap, so grains pile up on the bottom edge
+    pub fn sand() -> Self {
+        let mut table = [0; 16];
+        for (block, next) in table.iter_mut().enumerate() {
+            let mut block = block as u8;
+            for (top, bottom) in [(TOP_LEFT, BOTTOM_LEFT), (TOP_RIGHT, BOTTOM_RIGHT)] {
+                if block & top != 0 && block & bottom == 0 {
+                    block = block & !top | bottom;
+                }
+            }
+            if block == TOP_LEFT | BOTTOM_LEFT || block == TOP_RIGHT | BOTTOM_RIGHT {
+                block = BOTTOM_LEFT | BOTTOM_RIGHT;
+            }
+            *next = block;
+        }
+        MargolusRule::new("Sand", table, false)
+    }
+
+    // A preset (critters, bbm, sand) or 16 comma-separated block numbers,
+    // optionally followed by ",nowrap" for a board with hard edges
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        match spec.trim().to_ascii_lowercase().as_str() {
+            "critters" => return Ok(MargolusRule::critters()),
+            "bbm" | "billiards" => return Ok(MargolusRule::billiard_balls()),
+            "sand" => return Ok(MargolusRule::sand()),
+            _ => {},
+        }
+
+        let mut fields: Vec<&str> = spec.split(',').map(str::trim).collect();
+        let wraps = fields.last() != Some(&"nowrap");
+        if !wraps {
+            fields.pop();
+        }
+        let entries: Vec<u8> = fields
+            .iter()
+            .map(|field| match field.parse::<u8>() {
+                Ok(block) if block < 16 => Ok(block),
+                _ => Err(format!("'{}' is not a block number between 0 and 15", field)),
+            })
+            .collect::<Result<_, _>>()?;
+        let table: [u8; 16] = entries
+            .try_into()
+            .map_err(|entries: Vec<u8>| format!("a block table needs 16 entries, got {}", entries.len()))?;
+        Ok(MargolusRule::new("Custom", table, wraps))
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    // Next board from the current one. Even phases use blocks with their top-left
+    // cell on even coordinates, odd phases shift the blocks by one cell
+    pub fn step(&self, grid: &[Vec<bool>], phase: u64) -> Vec<Vec<bool>> {
+        let height = grid.len() as isize;
+        let width = grid.first().map_or(0, |row| row.len()) as isize;
+        let offset = (phase % 2) as isize;
+        let mut next = grid.to_vec();
+
+        // Blocks on odd phases either wrap around the edges or, on a board with
+        // walls, hang over them on both sides
+        let first = if self.wraps { offset } else { -offset };
+        for top in (first..height).step_by(2) {
+            for left in (first..width).step_by(2) {
+                let (mut right, mut bottom) = (left + 1, top + 1);
+                if self.wraps {
+                    right %= width;
+                    bottom %= height;
+                }
+
+                // Cells past a wall count as empty, and a block whose update would
+                // move cells past the wall is left as it is
+                let corners = [(left, top, TOP_LEFT), (right, top, TOP_RIGHT), (left, bottom, BOTTOM_LEFT), (right, bottom, BOTTOM_RIGHT)];
+                let inside = |&(x, y, _): &(isize, isize, u8)| (0..width).contains(&x) && (0..height).contains(&y);
+                let block = corners
+                    .iter()
+                    .filter(|corner| inside(corner) && grid[corner.1 as usize][corner.0 as usize])
+                    .fold(0, |block, &(_, _, bit)| block | bit);
+                let result = self.table[block as usize];
+                if corners.iter().any(|corner| !inside(corner) && result & corner.2 != 0) {
+                    continue;
+                }
+                for corner in corners.iter().filter(|corner| inside(corner)) {
+                    next[corner.1 as usize][corner.0 as usize] = result & corner.2 != 0;
+                }
+            }
+        }
+        next
+    }
+}
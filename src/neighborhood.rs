@@ -0,0 +1,235 @@
+use std::fmt;
+
+// Neighbourhood a rule counts live cells in
+#[derive(Clone, PartialEq)]
+pub enum Neighborhood {
+    Moore(u32),              // Square of range r (Larger than Life); range 1 is the classic 8 cells
+    VonNeumann(u32),         // Diamond of cells with |dx| + |dy| <= r
+    Hexagonal(u32),          // Hex cells within distance r, on a grid whose odd rows are shifted half a cell right
+    Weighted(Vec<Vec<u32>>), // (2r+1) x (2r+1) weights centred on the cell, centre weight included
+}
+
+// A horizontal run of cells sharing one weight: row dy, columns dx0..=dx1
+#[derive(Clone, Copy)]
+struct Run {
+    dy: i32,
+    dx0: i32,
+    dx1: i32,
+    weight: u32,
+}
+
+impl Neighborhood {
+    pub fn range(&self) -> u32 {
+        match self {
+            Neighborhood::Moore(r) | Neighborhood::VonNeumann(r) | Neighborhood::Hexagonal(r) => *r,
+            Neighborhood::Weighted(weights) => (weights.len() as u32).saturating_sub(1) / 2,
+        }
+    }
+
+    // Weighted cells around a cell on an even or odd row; the centre is only
+    // included for weighted masks, where it carries its own weight
+    pub fn cells(&self, odd_row: bool) -> Vec<(i32, i32, u32)> {
+        let r = self.range() as i32;
+        let mut cells = Vec::new();
+        for dy in -r..=r {
+            for dx in -r..=r {
+                let weight = match self {
+                    Neighborhood::Moore(_) => 1,
+                    Neighborhood::VonNeumann(_) => (dx.abs() + dy.abs() <= r) as u32,
+                    Neighborhood::Hexagonal(_) => (hex_distance(dx, dy, odd_row) <= r) as u32,
+                    Neighborhood::Weighted(weights) => weights[(dy + r) as usize][(dx + r) as usize],
+                };
+                let centre = dx == 0 && dy == 0;
+                if weight > 0 && (!centre || matches!(self, Neighborhood::Weighted(_))) {
+                    cells.push((dx, dy, weight));
+                }
+            }
+        }
+        cells
+    }
+
+    // Largest sum the neighbourhood can produce (the centre only counts for weighted masks)
+    pub fn max_sum(&self) -> u32 {
+        self.cells(false).iter().map(|&(_, _, weight)| weight).sum()
+    }
+
+    // Merge the neighbourhood into horizontal runs of equal weight, so each run
+    // costs a single summed-area table lookup however wide it is
+    fn runs(&self, odd_row: bool) -> Vec<Run> {
+        let mut runs: Vec<Run> = Vec::new();
+        for (dx, dy, weight) in self.cells(odd_row) {
+            match runs.last_mut() {
+                Some(run) if run.dy == dy && run.dx1 + 1 == dx && run.weight == weight => run.dx1 = dx,
+                _ => runs.push(Run { dy, dx0: dx, dx1: dx, weight }),
+            }
+        }
+        runs
+    }
+}
+
+impl fmt::Display for Neighborhood {
+    // HROT neighbourhood suffix: M, N, H or W followed by one hex digit per weight
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Neighborhood::Moore(_) => write!(f, "M"),
+            Neighborhood::VonNeumann(_) => write!(f, "N"),
+            Neighborhood::Hexagonal(_) => write!(f, "H"),
+            Neighborhood::Weighted(weights) => {
+                write!(f, "W")?;
+                for weight in weights.iter().flatten() {
+                    write!(f, "{:X}", weight)?;
+                }
+                Ok(())
+            },
+        }
+    }
+}
+
+// Parse an HROT neighbourhood type ("M", "N", "H" or "W" plus hex weights) for range r
+pub fn parse_neighborhood(spec: &str, range: u32) -> Result<Neighborhood, String> {
+    match spec.chars().next() {
+        Some('M') if spec.len() == 1 => Ok(Neighborhood::Moore(range)),
+        Some('N') if spec.len() == 1 => Ok(Neighborhood::VonNeumann(range)),
+        Some('H') if spec.len() == 1 => Ok(Neighborhood::Hexagonal(range)),
+        Some('W') => {
+            let side = 2 * range as usize + 1;
+            let digits: Vec<u32> = spec[1..]
+                .chars()
+                .map(|c| c.to_digit(16).ok_or(format!("'{}' is not a hex weight", c)))
+                .collect::<Result<_, _>>()?;
+            if digits.len() != side * side {
+                return Err(format!("range {} needs {} weights, got {}", range, side * side, digits.len()));
+            }
+            Ok(Neighborhood::Weighted(digits.chunks(side).map(|row| row.to_vec()).collect()))
+        },
+        _ => Err(format!("'{}' is not a neighbourhood type", spec)),
+    }
+}
+
+// Offset of a cell in hex steps, with odd rows shifted half a cell to the right
+fn hex_distance(dx: i32, dy: i32, odd_row: bool) -> i32 {
+    // Convert both cells to axial coordinates and take the cube distance
+    let axial = |x: i32, y: i32| (x - (y - (y & 1)) / 2, y);
+    let y0 = odd_row as i32;
+    let (q0, r0) = axial(0, y0);
+    let (q1, r1) = axial(dx, y0 + dy);
+    let (dq, dr) = (q1 - q0, r1 - r0);
+    (dq.abs() + dr.abs() + (dq + dr).abs()) / 2
+}
+
+// Weighted count of live neighbours for every cell of a toroidal board, plus the
+// cell itself when include_centre is set (weighted masks carry their own centre).
+// A summed-area table over the board padded with its own wrapped edges turns
+// every run of the neighbourhood into an O(1) rectangle lookup
+pub fn neighbor_sums(alive: &[Vec<bool>], neighborhood: &Neighborhood, include_centre: bool) -> Vec<Vec<u32>> {
+    let height = alive.len();
+    let width = alive.first().map_or(0, |row| row.len());
+    let pad = neighborhood.range() as usize;
+    let padded_width = width + 2 * pad;
+    let padded_height = height + 2 * pad;
+
+    // sat[y][x] is the number of live cells above and to the left of padded cell (x, y)
+    let mut sat = vec![vec![0u32; padded_width + 1]; padded_height + 1];
+    for py in 0..padded_height {
+        let y = (py + height * pad - pad) % height;
+        let mut row_sum = 0;
+        for px in 0..padded_width {
+            let x = (px + width * pad - pad) % width;
+            row_sum += alive[y][x] as u32;
+            sat[py + 1][px + 1] = sat[py][px + 1] + row_sum;
+        }
+    }
+    let rect = |x0: usize, x1: usize, y0: usize, y1: usize| {
+        sat[y1 + 1][x1 + 1] + sat[y0][x0] - sat[y0][x1 + 1] - sat[y1 + 1][x0]
+    };
+
+    let even_runs = neighborhood.runs(false);
+    let odd_runs = neighborhood.runs(true);
+    let mut sums = vec![vec![0u32; width]; height];
+    for (y, row) in sums.iter_mut().enumerate() {
+        let runs = if y % 2 == 1 { &odd_runs } else { &even_runs };
+        for (x, sum) in row.iter_mut().enumerate() {
+            let (px, py) = ((x + pad) as i32, (y + pad) as i32);
+            *sum = match neighborhood {
+                // The whole square is a single lookup
+                Neighborhood::Moore(r) => {
+                    let r = *r as i32;
+                    let square = rect((px - r) as usize, (px + r) as usize, (py - r) as usize, (py + r) as usize);
+                    square - alive[y][x] as u32
+                },
+                _ => runs
+                    .iter()
+                    .map(|run| {
+                        let row_y = (py + run.dy) as usize;
+                        run.weight * rect((px + run.dx0) as usize, (px + run.dx1) as usize, row_y, row_y)
+                    })
+                    .sum(),
+            };
+            if include_centre && alive[y][x] && !matches!(neighborhood, Neighborhood::Weighted(_)) {
+                *sum += 1;
+            }
+        }
+    }
+    sums
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    // Sum every cell of the neighbourhood one by one, wrapping around the edges
+    fn brute_force_sums(alive: &[Vec<bool>], neighborhood: &Neighborhood, include_centre: bool) -> Vec<Vec<u32>> {
+        let height = alive.len() as i32;
+        let width = alive[0].len() as i32;
+        let mut sums = vec![vec![0u32; width as usize]; height as usize];
+        for (y, row) in sums.iter_mut().enumerate() {
+            for (x, sum) in row.iter_mut().enumerate() {
+                for (dx, dy, weight) in neighborhood.cells(y % 2 == 1) {
+                    let nx = (x as i32 + dx).rem_euclid(width) as usize;
+                    let ny = (y as i32 + dy).rem_euclid(height) as usize;
+                    *sum += weight * alive[ny][nx] as u32;
+                }
+                if include_centre && alive[y][x] && !matches!(neighborhood, Neighborhood::Weighted(_)) {
+                    *sum += 1;
+                }
+            }
+        }
+        sums
+    }
+
+    #[test]
+    fn sums_match_brute_force() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let neighborhoods = [
+            Neighborhood::Moore(1),
+            Neighborhood::Moore(3),
+            Neighborhood::VonNeumann(1),
+            Neighborhood::VonNeumann(4),
+            Neighborhood::Hexagonal(1),
+            Neighborhood::Hexagonal(3),
+            parse_neighborhood("W010121010", 1).unwrap(),
+            parse_neighborhood("W1234567890ABCDEF012345678", 2).unwrap(),
+        ];
+        // Even heights keep the hex rows alternating across the wrap; the small
+        // board is narrower than the larger neighbourhoods and wraps several times
+        for (width, height) in [(23, 18), (3, 4)] {
+            let alive: Vec<Vec<bool>> = (0..height).map(|_| (0..width).map(|_| rng.gen_bool(0.4)).collect()).collect();
+            for neighborhood in &neighborhoods {
+                for include_centre in [false, true] {
+                    assert_eq!(
+                        neighbor_sums(&alive, neighborhood, include_centre),
+                        brute_force_sums(&alive, neighborhood, include_centre),
+                        "{} range {} centre {} on {}x{}",
+                        neighborhood,
+                        neighborhood.range(),
+                        include_centre,
+                        width,
+                        height,
+                    );
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,195 @@
+use std::path::Path;
+use crate::{lif, plaintext, rle};
+use crate::macrocell::Macrocell;
+
+// Largest pattern read, in cells of its bounding box
+pub const MAX_CELLS: usize = 16_000_000;
+
+// A pattern read from or written to a file: a rectangle of cell states
+// (0 is dead) plus whatever the file said about it
+#[derive(Clone, Default)]
+pub struct Pattern {
+    pub name: Option<String>,
+    pub author: Option<String>,
+    pub comments: Vec<String>,
+    pub rule: Option<String>,
+    pub cells: Vec<Vec<u8>>, // Rows of states, all the same width
+}
+
+impl Pattern {
+    // The smallest rectangle around the live cells of a board
+    pub fn from_board(states: &[Vec<u8>], rule: Option<String>) -> Self {
+        let live = || states.iter().enumerate().flat_map(|(y, row)| row.iter().enumerate().filter(|(_, s)| **s != 0).map(move |(x, _)| (x, y)));
+        let (Some(left), Some(right)) = (live().map(|c| c.0).min(), live().map(|c| c.0).max()) else {
+            return Pattern { rule, ..Pattern::default() };
+        };
+        let top = live().map(|c| c.1).min().unwrap_or(0);
+        let bottom = live().map(|c| c.1).max().unwrap_or(0);
+        let cells = states[top..=bottom].iter().map(|row| row[left..=right].to_vec()).collect();
+        Pattern { rule, cells, ..Pattern::default() }
+    }
+
+    // The smallest rectangle around cells of given states anywhere on the plane
+    pub fn from_points(points: &[(i64, i64, u8)]) -> Result<Self, String> {
+        let (Some(left), Some(right)) = (points.iter().map(|p| p.0).min(), points.iter().map(|p| p.0).max()) else {
+            return Ok(Pattern::default());
+        };
+        let top = points.iter().map(|p| p.1).min().unwrap_or(0);
+        let bottom = points.iter().map(|p| p.1).max().unwrap_or(0);
+        // Cells as far apart as i64::MIN and i64::MAX don't fit in a usize side
+        let side = |low: i64, high: i64| high.checked_sub(low).and_then(|span| usize::try_from(span).ok()?.checked_add(1));
+        let (Some(width), Some(height)) = (side(left, right), side(top, bottom)) else {
+            return Err(format!("cells from {},{} to {},{} are too far apart to load", left, top, right, bottom));
+        };
+        if width.saturating_mul(height) > MAX_CELLS {
+            return Err(format!("{}x{} is too big to load", width, height));
+        }
+        let mut cells = vec![vec![0; width]; height];
+        for &(x, y, state) in points {
+            cells[(y - top) as usize][(x - left) as usize] = state;
+        }
+        Ok(Pattern { cells, ..Pattern::default() })
+    }
+
+    pub fn width(&self) -> usize {
+        self.cells.first().map_or(0, |row| row.len())
+    }
+
+    pub fn height(&self) -> usize {
+        self.cells.len()
+    }
+
+    // Pad every row with dead cells to the longest one
+    pub fn pad_rows(&mut self) {
+        let width = self.cells.iter().map(|row| row.len()).max().unwrap_or(0);
+        for row in &mut self.cells {
+            row.resize(width, 0);
+        }
+    }
+}
+
+// Whether a rule written in a pattern file is plain Life; files without one are taken to be
+pub fn is_life(rule: Option<&str>) -> bool {
+    rule.is_none_or(|rule| rule.eq_ignore_ascii_case("B3/S23") || rule == "23/3")
+}
+
+// Pattern file formats
+#[derive(Clone, Copy, PartialEq)]
+pub enum Format {
+    Rle,       // Run Length Encoded (.rle)
+    Plaintext, // Rows of . and O (.cells)
+    Life105,   // Blocks of . and * placed by #P lines (.lif, .life)
+    Life106,   // One "x y" line per live cell (.lif, .life)
+    Macrocell, // Golly quadtree (.mc)
+}
+
+impl Format {
+    // The format of a file: the #Life or [M2] header if it has one, otherwise the
+    // extension, otherwise a guess from the first line that isn't a comment
+    pub fn detect(path: &str, text: &str) -> Result<Self, String> {
+        match text.lines().next().map(str::trim) {
+            Some(line) if line.starts_with("#Life 1.05") => return Ok(Format::Life105),
+            Some(line) if line.starts_with("#Life 1.06") => return Ok(Format::Life106),
+            Some(line) if line.starts_with("[M2]") => return Ok(Format::Macrocell),
+            _ => {},
+        }
+        if let Some(format) = Format::from_extension(path) {
+            return Ok(format);
+        }
+        let first = text.lines().map(str::trim).find(|line| !line.is_empty() && !line.starts_with('#') && !line.starts_with('!'));
+        match first {
+            Some(line) if line.starts_with('x') && line.contains('=') => Ok(Format::Rle),
+            Some(line) if line.chars().all(|c| matches!(c, '.' | 'O' | '*')) => Ok(Format::Plaintext),
+            Some(line) if line.split_whitespace().count() == 2 && line.split_whitespace().all(|n| n.parse::<i64>().is_ok()) => Ok(Format::Life106),
+            _ => Err("not in a pattern format I know (RLE, .cells, Life 1.05/1.06 or macrocell)".to_string()),
+        }
+    }
+
+    // The format an extension stands for. Both Life formats use .lif, and
+    // files are written as Life 1.06 there
+    pub fn from_extension(path: &str) -> Option<Self> {
+        let extension = Path::new(path).extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "rle" => Some(Format::Rle),
+            "cells" => Some(Format::Plaintext),
+            "lif" | "life" => Some(Format::Life106),
+            "mc" => Some(Format::Macrocell),
+            _ => None,
+        }
+    }
+
+    // Macrocell files are flattened, so they have to be small enough to hold cell by cell
+    pub fn parse(self, text: &str) -> Result<Pattern, String> {
+        match self {
+            Format::Rle => rle::parse(text),
+            Format::Plaintext => plaintext::parse(text),
+            Format::Life105 => lif::parse_105(text),
+            Format::Life106 => lif::parse_106(text),
+            Format::Macrocell => Macrocell::parse(text)?.to_pattern(),
+        }
+    }
+
+    pub fn write(self, pattern: &Pattern) -> String {
+        match self {
+            Format::Rle => rle::write(pattern),
+            Format::Plaintext => plaintext::write(pattern),
+            Format::Life105 => lif::write_105(pattern),
+            Format::Life106 => lif::write_106(pattern),
+            Format::Macrocell => Macrocell::from_pattern(pattern).write(),
+        }
+    }
+}
+
+// Read a pattern file in whichever format it is in
+pub fn load(path: &str) -> Result<Pattern, String> {
+    let text = std::fs::read_to_string(path).map_err(|err| err.to_string())?;
+    Format::detect(path, &text)?.parse(&text)
+}
+
+// Write a pattern in the format its extension asks for, RLE when there is none
+pub fn save(path: &str, pattern: &Pattern) -> Result<(), String> {
+    let format = Format::from_extension(path).unwrap_or(Format::Rle);
+    std::fs::write(path, format.write(pattern)).map_err(|err| format!("{}: {}", path, err))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn far_apart_points_are_rejected() {
+        assert!(Pattern::from_points(&[(i64::MIN, 0, 1), (i64::MAX, 0, 1)]).is_err());
+        assert!(Pattern::from_points(&[(0, i64::MIN, 1), (0, i64::MAX, 1)]).is_err());
+        assert!(Pattern::from_points(&[(0, 0, 1), (5000, 5000, 1)]).is_err());
+
+        let pattern = Pattern::from_points(&[(i64::MAX, i64::MIN, 1), (i64::MAX - 2, i64::MIN + 1, 2)]).unwrap();
+        assert_eq!(pattern.cells, vec![vec![0, 0, 1], vec![2, 0, 0]]);
+        assert_eq!(Pattern::from_points(&[]).unwrap().cells, Vec::<Vec<u8>>::new());
+    }
+
+    #[test]
+    fn formats_detected_by_header() {
+        // The header wins over the extension
+        assert!(Format::detect("glider.rle", "#Life 1.05\n#P 0 0\n*\n") == Ok(Format::Life105));
+        assert!(Format::detect("glider.cells", "#Life 1.06\n0 0\n") == Ok(Format::Life106));
+        assert!(Format::detect("glider.lif", "[M2] (golly 4.2)\n") == Ok(Format::Macrocell));
+    }
+
+    #[test]
+    fn formats_detected_by_extension() {
+        assert!(Format::detect("glider.rle", "") == Ok(Format::Rle));
+        assert!(Format::detect("glider.CELLS", "") == Ok(Format::Plaintext));
+        assert!(Format::detect("glider.lif", "0 0\n") == Ok(Format::Life106));
+        assert!(Format::detect("glider.life", "") == Ok(Format::Life106));
+        assert!(Format::detect("glider.mc", "") == Ok(Format::Macrocell));
+    }
+
+    #[test]
+    fn formats_detected_by_content() {
+        assert!(Format::detect("glider", "#N Glider\nx = 3, y = 3\nbo$2bo$3o!\n") == Ok(Format::Rle));
+        assert!(Format::detect("glider.txt", "!Name: Glider\n.O\n..O\nOOO\n") == Ok(Format::Plaintext));
+        assert!(Format::detect("glider", "\n# Glider\n1 0\n2 1\n") == Ok(Format::Life106));
+        assert!(Format::detect("glider", "hello\n").is_err());
+        assert!(Format::detect("glider", "").is_err());
+    }
+}
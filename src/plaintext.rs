@@ -0,0 +1,99 @@
+use crate::pattern::{Pattern, MAX_CELLS};
+
+// Read a plaintext (.cells) pattern: ! lines are comments, "!Name:" and
+// "!Author:" among them, and every other line is a row of . for dead and O for
+// live cells (* is taken too). Rows may stop at their last live cell
+pub fn parse(text: &str) -> Result<Pattern, String> {
+    let mut pattern = Pattern::default();
+    let mut cells = 0usize;
+
+    for line in text.lines() {
+        let line = line.trim_end();
+        if let Some(comment) = line.strip_prefix('!') {
+            let comment = comment.trim();
+            if let Some(name) = comment.strip_prefix("Name:") {
+                pattern.name = Some(name.trim().to_string());
+            } else if let Some(author) = comment.strip_prefix("Author:") {
+                pattern.author = Some(author.trim().to_string());
+            } else {
+                pattern.comments.push(comment.to_string());
+            }
+            continue;
+        }
+        // Blank lines before the first row are only spacing
+        if line.is_empty() && pattern.cells.is_empty() {
+            continue;
+        }
+        let row = line
+            .chars()
+            .map(|c| match c {
+                '.' => Ok(0),
+                'O' | '*' => Ok(1),
+                c => Err(format!("unexpected '{}' in row {}", c, pattern.cells.len() + 1)),
+            })
+            .collect::<Result<Vec<u8>, String>>()?;
+        cells += row.len().max(1);
+        if cells > MAX_CELLS {
+            return Err("too big to load".to_string());
+        }
+        pattern.cells.push(row);
+    }
+
+    while pattern.cells.last().is_some_and(|row| row.iter().all(|&state| state == 0)) {
+        pattern.cells.pop();
+    }
+    pattern.pad_rows();
+    Ok(pattern)
+}
+
+// Write a pattern as plaintext. The format has only two states, so every live
+// state is written as O, and no rule
+pub fn write(pattern: &Pattern) -> String {
+    let mut text = String::new();
+    if let Some(name) = &pattern.name {
+        text += &format!("!Name: {}\n", name);
+    }
+    if let Some(author) = &pattern.author {
+        text += &format!("!Author: {}\n", author);
+    }
+    for comment in &pattern.comments {
+        text += &format!("!{}\n", comment);
+    }
+    for row in &pattern.cells {
+        let length = row.iter().rposition(|&state| state != 0).map_or(0, |last| last + 1);
+        if length == 0 {
+            text += ".";
+        }
+        text.extend(row[..length].iter().map(|&state| if state == 0 { '.' } else { 'O' }));
+        text += "\n";
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips() {
+        let pattern = Pattern {
+            name: Some("Glider".to_string()),
+            author: Some("Richard K. Guy".to_string()),
+            comments: vec!["The smallest spaceship".to_string()],
+            cells: vec![vec![0, 1, 0], vec![0, 0, 0], vec![1, 1, 1]],
+            ..Pattern::default()
+        };
+        let read = parse(&write(&pattern)).unwrap();
+        assert_eq!(read.cells, pattern.cells);
+        assert_eq!(read.name, pattern.name);
+        assert_eq!(read.author, pattern.author);
+        assert_eq!(read.comments, pattern.comments);
+    }
+
+    #[test]
+    fn short_rows_are_padded() {
+        let read = parse("!Name: Glider\n\n.O\n..O\nOOO\n\n").unwrap();
+        assert_eq!(read.cells, vec![vec![0, 1, 0], vec![0, 0, 1], vec![1, 1, 1]]);
+        assert!(parse("OOx\n").is_err());
+    }
+}
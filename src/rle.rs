@@ -0,0 +1,251 @@
+use crate::pattern::{Pattern, MAX_CELLS};
+
+// Longest line written, as Golly and the LifeWiki do
+const LINE_LENGTH: usize = 70;
+
+// Read a Run Length Encoded pattern: # lines (#N name, #O author, #C comments),
+// the "x = 3, y = 3, rule = B3/S23" header, then runs of cell states separated
+// by $ at row ends and finished by !. Two-state files use b and o; multi-state
+// ones use . for dead, A to X for states 1 to 24 and a prefix p to y for the
+// states above, so pA is 25 and yO is 255
+pub fn parse(text: &str) -> Result<Pattern, String> {
+    let mut pattern = Pattern::default();
+    let mut size = None;
+    let mut lines = text.lines();
+
+    for line in lines.by_ref() {
+        let line = line.trim();
+        if let Some(comment) = line.strip_prefix('#') {
+            let (kind, content) = comment.split_at(comment.chars().next().map_or(0, char::len_utf8));
+            let content = content.trim().to_string();
+            match kind {
+                "N" => pattern.name = Some(content),
+                "O" => pattern.author = Some(content),
+                "C" | "c" => pattern.comments.push(content),
+                // Old files give the rule on a line of its own
+                "r" => pattern.rule = Some(content),
+                // Offsets (#P, #R) don't matter once the pattern is placed by hand
+                _ => {},
+            }
+        } else if line.is_empty() {
+            continue;
+        } else if line.starts_with('x') {
+            size = Some(parse_header(line, &mut pattern)?);
+            break;
+        } else {
+            return Err(format!("expected the x = , y = header, found \"{}\"", line));
+        }
+    }
+    let (width, height) = size.ok_or("missing the x = , y = header")?;
+    if width.saturating_mul(height) > MAX_CELLS {
+        return Err(format!("{}x{} is too big to load", width, height));
+    }
+
+    let mut rows: Vec<Vec<u8>> = vec![Vec::new()];
+    let mut count: Option<usize> = None;
+    let mut prefix: Option<u8> = None;
+    let mut finished = false;
+    'data: for line in lines {
+        for c in line.trim().chars() {
+            let state = match c {
+                '0'..='9' => {
+                    let digit = c.to_digit(10).unwrap_or(0) as usize;
+                    count = Some(count.unwrap_or(0).checked_mul(10).and_then(|n| n.checked_add(digit)).ok_or("run count too large")?);
+                    continue;
+                },
+                'p'..='y' if prefix.is_none() => {
+                    prefix = Some(c as u8 - b'p' + 1);
+                    continue;
+                },
+                'b' | '.' => 0,
+                'o' => 1,
+                'A'..='X' => (prefix.take().unwrap_or(0) as usize * 24 + (c as u8 - b'A') as usize + 1).min(255) as u8,
+                '$' => {
+                    let run = count.take().unwrap_or(1);
+                    if rows.len() + run > height + 1 {
+                        return Err(format!("more rows than the {} in the header", height));
+                    }
+                    rows.extend(std::iter::repeat_n(Vec::new(), run));
+                    continue;
+                },
+                '!' => {
+                    finished = true;
+                    break 'data;
+                },
+                c if c.is_whitespace() => continue,
+                // Two-state files may use any other letter for live cells
+                c if c.is_ascii_lowercase() => 1,
+                c => return Err(format!("unexpected '{}' in the pattern", c)),
+            };
+            if prefix.is_some() {
+                return Err(format!("state prefix not followed by a letter from A to X before '{}'", c));
+            }
+            let run = count.take().unwrap_or(1);
+            let row = rows.last_mut().expect("rows start with one row");
+            if row.len() + run > width {
+                return Err(format!("row {} is wider than the {} cells in the header", rows.len(), width));
+            }
+            row.extend(std::iter::repeat_n(state, run));
+        }
+    }
+    if !finished {
+        return Err("pattern not finished with !".to_string());
+    }
+
+    // Rows may stop short of the header's size, and a $ before the ! can leave
+    // one empty row past it
+    rows.truncate(height);
+    rows.resize(height, Vec::new());
+    for row in &mut rows {
+        row.resize(width, 0);
+    }
+    pattern.cells = rows;
+    Ok(pattern)
+}
+
+// Read "x = 3, y = 3, rule = B3/S23" into the pattern size and rule. Rules may
+// have commas of their own, as in "rule = R5,C2,M1,S34-58,B34-45,NM"
+fn parse_header(line: &str, pattern: &mut Pattern) -> Result<(usize, usize), String> {
+    let (mut width, mut height) = (None, None);
+    let mut in_rule = false;
+    for field in line.split(',') {
+        let Some((key, value)) = field.split_once('=') else {
+            match &mut pattern.rule {
+                Some(rule) if in_rule => {
+                    rule.push(',');
+                    rule.push_str(field.trim());
+                    continue;
+                },
+                _ => return Err(format!("expected key = value in the header, found \"{}\"", field.trim())),
+            }
+        };
+        let value = value.trim();
+        in_rule = key.trim() == "rule";
+        match key.trim() {
+            "x" => width = Some(value.parse::<usize>().map_err(|_| format!("bad width \"{}\"", value))?),
+            "y" => height = Some(value.parse::<usize>().map_err(|_| format!("bad height \"{}\"", value))?),
+            "rule" => pattern.rule = Some(value.to_string()),
+            _ => {},
+        }
+    }
+    match (width, height) {
+        (Some(width), Some(height)) => Ok((width, height)),
+        _ => Err("header needs both x and y".to_string()),
+    }
+}
+
+// Write a pattern as RLE, with b and o when it only has two states. Dead cells
+// at row ends are left out, empty rows are folded into the $ count and lines
+// are wrapped without splitting a run
+pub fn write(pattern: &Pattern) -> String {
+    let mut text = String::new();
+    if let Some(name) = &pattern.name {
+        text += &format!("#N {}\n", name);
+    }
+    if let Some(author) = &pattern.author {
+        text += &format!("#O {}\n", author);
+    }
+    for comment in &pattern.comments {
+        text += &format!("#C {}\n", comment);
+    }
+    text += &format!("x = {}, y = {}", pattern.width(), pattern.height());
+    if let Some(rule) = &pattern.rule {
+        text += &format!(", rule = {}", rule);
+    }
+    text += "\n";
+
+    let two_states = pattern.cells.iter().flatten().all(|&state| state <= 1);
+    let mut runs: Vec<String> = Vec::new();
+    let mut row_ends = 0;
+    for row in &pattern.cells {
+        let length = row.iter().rposition(|&state| state != 0).map_or(0, |last| last + 1);
+        if length > 0 && row_ends > 0 {
+            runs.push(token(row_ends, "$"));
+            row_ends = 0;
+        }
+        let mut x = 0;
+        while x < length {
+            let run = row[x..length].iter().take_while(|&&state| state == row[x]).count();
+            runs.push(token(run, &letter(row[x], two_states)));
+            x += run;
+        }
+        row_ends += 1;
+    }
+    runs.push("!".to_string());
+
+    let mut line = String::new();
+    for run in runs {
+        if line.len() + run.len() > LINE_LENGTH {
+            text += &line;
+            text += "\n";
+            line.clear();
+        }
+        line += &run;
+    }
+    text + &line + "\n"
+}
+
+// A run of one is written without its count
+fn token(run: usize, letter: &str) -> String {
+    if run == 1 { letter.to_string() } else { format!("{}{}", run, letter) }
+}
+
+fn letter(state: u8, two_states: bool) -> String {
+    match state {
+        0 if two_states => "b".to_string(),
+        1 if two_states => "o".to_string(),
+        0 => ".".to_string(),
+        _ => {
+            let (prefix, letter) = ((state - 1) / 24, (state - 1) % 24);
+            let letter = (b'A' + letter) as char;
+            if prefix == 0 { letter.to_string() } else { format!("{}{}", (b'p' + prefix - 1) as char, letter) }
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip(pattern: &Pattern) {
+        let read = parse(&write(pattern)).unwrap();
+        assert_eq!(read.cells, pattern.cells);
+        assert_eq!(read.rule, pattern.rule);
+        assert_eq!(read.name, pattern.name);
+        assert_eq!(read.comments, pattern.comments);
+    }
+
+    #[test]
+    fn two_states_round_trip() {
+        round_trip(&Pattern {
+            name: Some("Glider".to_string()),
+            comments: vec!["The smallest spaceship".to_string()],
+            rule: Some("B3/S23".to_string()),
+            cells: vec![vec![0, 1, 0], vec![0, 0, 1], vec![1, 1, 1]],
+            ..Pattern::default()
+        });
+    }
+
+    #[test]
+    fn multi_state_round_trip() {
+        // States past 24 need a prefix letter, 255 is the last one
+        round_trip(&Pattern {
+            rule: Some("/2/3".to_string()),
+            cells: vec![vec![1, 2, 0, 0], vec![0, 0, 0, 0], vec![24, 25, 48, 255]],
+            ..Pattern::default()
+        });
+    }
+
+    #[test]
+    fn rules_with_commas_round_trip() {
+        let pattern = Pattern {
+            rule: Some("R5,C2,M1,S34-58,B34-45,NM".to_string()),
+            cells: vec![vec![1, 1]],
+            ..Pattern::default()
+        };
+        round_trip(&pattern);
+        let read = parse("x = 2, y = 1, rule = R5,C2,M1,S34-58,B34-45,NM\n2o!\n").unwrap();
+        assert_eq!(read.rule.as_deref(), Some("R5,C2,M1,S34-58,B34-45,NM"));
+        assert!(parse("x = 2, y, rule = B3/S23\n2o!\n").is_err());
+    }
+}
@@ -0,0 +1,517 @@
+use std::path::Path;
+use crate::library::{Category, Library};
+use crate::pattern::{self, Pattern};
+use crate::soup::{Soup, Symmetry};
+use crate::transform::{Orientation, Transform, MAX_PHASE};
+
+// Largest grid side a scenario may ask for
+const MAX_GRID_SIDE: usize = 1000;
+// Soup density when a region doesn't give one
+const DEFAULT_DENSITY: f64 = 0.02;
+
+// How the edges of the board behave
+#[derive(Clone, Copy, PartialEq)]
+pub enum Topology {
+    Torus, // Edges wrap around
+    Plane, // Unbounded, with the grid as a view of it
+}
+
+// Where a placed pattern comes from
+pub enum Source {
+    Library(String), // A library pattern by key or name
+    File(String),    // A pattern file, relative to the scenario file
+}
+
+// A pattern put down by a scenario, with its top-left corner on a grid cell
+pub struct Placed {
+    pub source: Source,
+    pub at: (usize, usize),
+    pub orientation: Orientation,
+    pub category: Option<Category>, // Colour category; the library's one otherwise
+}
+
+impl Placed {
+    // The pattern as it goes down, and the category it is coloured as. Still lifes
+    // and oscillators are back on the same cells every period, so their phase is
+    // taken modulo it; anything else, like a gun, is run to the phase itself
+    pub fn pattern(&self, library: &Library) -> Result<(Pattern, Category), String> {
+        let (pattern, category, period) = match &self.source {
+            Source::Library(name) => {
+                let entry = library.get(name).ok_or(format!("no pattern called '{}' in the library", name))?;
+                (entry.pattern.clone(), entry.category, entry.period)
+            },
+            Source::File(path) => (pattern::load(path).map_err(|err| format!("{}: {}", path, err))?, Category::Other, None),
+        };
+        let phase = match (category, period) {
+            (Category::StillLife | Category::Oscillator, Some(period)) => self.orientation.phase % period.max(1) as u64,
+            _ => self.orientation.phase,
+        };
+        let pattern = Orientation { phase, ..self.orientation }.apply(&pattern)?;
+        Ok((pattern, self.category.unwrap_or(category)))
+    }
+}
+
+// A random soup region. Without a seed of its own, it takes the scenario's
+// seed plus its position in the file, so a new scenario seed changes them all
+pub struct SoupRegion {
+    pub region: (usize, usize, usize, usize), // Left, top, width, height
+    pub density: f64,
+    pub symmetry: Symmetry,
+    pub seed: Option<u64>,
+}
+
+// A starting board described in a file: the grid, the rule and what goes on it
+pub struct Scenario {
+    pub width: usize,
+    pub height: usize,
+    pub rule: Option<String>, // Plain Life when there is none
+    pub topology: Topology,
+    pub seed: Option<u64>, // Random on every load when there is none
+    pub patterns: Vec<Placed>,
+    pub soups: Vec<SoupRegion>,
+}
+
+impl Scenario {
+    // Read a scenario file, with pattern files taken relative to it. The grid is
+    // default_size unless the file gives a width or height
+    pub fn load(path: &str, default_size: (usize, usize)) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path).map_err(|err| format!("{}: {}", path, err))?;
+        let mut scenario = Scenario::parse(&text, default_size).map_err(|err| format!("{}: {}", path, err))?;
+        let dir = Path::new(path).parent().unwrap_or(Path::new(""));
+        for placed in &mut scenario.patterns {
+            if let Source::File(file) = &mut placed.source {
+                *file = dir.join(&*file).to_string_lossy().into_owned();
+            }
+        }
+        Ok(scenario)
+    }
+
+    // A scenario in a subset of TOML: top-level width, height, rule, topology and
+    // seed keys, then a [[pattern]] table per placed pattern and a [[soup]] table
+    // per soup region, e.g.
+    //
+    //   width = 120
+    //   height = 80
+    //   rule = "B3/S23"
+    //   topology = "torus"
+    //   seed = 42
+    //
+    //   [[pattern]]
+    //   name = "glider"          # or file = "gun.rle"
+    //   at = [10, 10]
+    //   transform = "rotate90"
+    //   phase = 2
+    //   category = "spaceship"
+    //
+    //   [[soup]]
+    //   region = [60, 20, 30, 30]
+    //   density = 0.35
+    //   symmetry = "D4"
+    pub fn parse(text: &str, default_size: (usize, usize)) -> Result<Self, String> {
+        let mut scenario = Scenario {
+            width: default_size.0,
+            height: default_size.1,
+            rule: None,
+            topology: Topology::Torus,
+            seed: None,
+            patterns: Vec::new(),
+            soups: Vec::new(),
+        };
+
+        let mut pattern_tables = Vec::new();
+        let mut soup_tables = Vec::new();
+        for (table, header_line, entries) in parse_tables(text)? {
+            match table.as_str() {
+                "" => {
+                    for (line, key, value) in entries {
+                        let error = |err: String| format!("line {}: {}", line, err);
+                        match key.as_str() {
+                            "width" => scenario.width = value.side().map_err(error)?,
+                            "height" => scenario.height = value.side().map_err(error)?,
+                            "rule" => scenario.rule = Some(value.string().map_err(error)?.to_string()),
+                            "topology" => {
+                                scenario.topology = match value.string().map_err(error)? {
+                                    "torus" => Topology::Torus,
+                                    "plane" => Topology::Plane,
+                                    other => return Err(error(format!("'{}' is not a topology (use torus or plane)", other))),
+                                }
+                            },
+                            "seed" => scenario.seed = Some(value.unsigned().map_err(error)?),
+                            _ => return Err(error(format!("unknown key '{}'", key))),
+                        }
+                    }
+                },
+                "pattern" => pattern_tables.push((header_line, entries)),
+                "soup" => soup_tables.push(entries),
+                _ => return Err(format!("line {}: unknown table [[{}]] (use [[pattern]] or [[soup]])", header_line, table)),
+            }
+        }
+
+        // Positions are checked against the grid, so the tables are read after the size
+        for (header_line, entries) in pattern_tables {
+            scenario.patterns.push(scenario.parse_pattern(header_line, entries)?);
+        }
+        for entries in soup_tables {
+            scenario.soups.push(scenario.parse_soup(entries)?);
+        }
+        Ok(scenario)
+    }
+
+    fn parse_pattern(&self, header_line: usize, entries: Vec<(usize, String, Value)>) -> Result<Placed, String> {
+        let mut source = None;
+        let mut at = (0, 0);
+        let mut orientation = Orientation::default();
+        let mut category = None;
+        let mut phase_line = header_line;
+        for (line, key, value) in entries {
+            let error = |err: String| format!("line {}: {}", line, err);
+            match key.as_str() {
+                "name" => source = Some(Source::Library(value.string().map_err(error)?.to_string())),
+                "file" => source = Some(Source::File(value.string().map_err(error)?.to_string())),
+                "at" => {
+                    at = match value.numbers().map_err(error)?[..] {
+                        [x, y] => self.cell(x, y).map_err(error)?,
+                        _ => return Err(error("expected at = [x, y]".to_string())),
+                    }
+                },
+                "transform" => orientation.transform = Transform::parse(value.string().map_err(error)?).map_err(error)?,
+                "phase" => {
+                    orientation.phase = value.unsigned().map_err(error)?;
+                    phase_line = line;
+                },
+                "category" => category = Some(Category::parse(value.string().map_err(error)?).map_err(error)?),
+                _ => return Err(error(format!("unknown pattern key '{}'", key))),
+            }
+        }
+        let source = source.ok_or(format!("line {}: a [[pattern]] needs a name or a file", header_line))?;
+        // Library still lifes and oscillators have their phase brought down by
+        // their period later, so only the library knows whether theirs is too far
+        if matches!(source, Source::File(_)) && orientation.phase > MAX_PHASE {
+            return Err(format!("line {}: phase {} is past the last one, {}", phase_line, orientation.phase, MAX_PHASE));
+        }
+        Ok(Placed { source, at, orientation, category })
+    }
+
+    fn parse_soup(&self, entries: Vec<(usize, String, Value)>) -> Result<SoupRegion, String> {
+        let mut soup = SoupRegion { region: (0, 0, self.width, self.height), density: DEFAULT_DENSITY, symmetry: Symmetry::C1, seed: None };
+        for (line, key, value) in entries {
+            let error = |err: String| format!("line {}: {}", line, err);
+            match key.as_str() {
+                "region" => {
+                    let (width, height) = (self.width as i64, self.height as i64);
+                    soup.region = match value.numbers().map_err(error)?[..] {
+                        [left, top, w, h] if left >= 0 && top >= 0 && w > 0 && h > 0 && left + w <= width && top + h <= height => {
+                            (left as usize, top as usize, w as usize, h as usize)
+                        },
+                        [_, _, _, _] => return Err(error(format!("region does not fit the {}x{} grid", width, height))),
+                        _ => return Err(error("expected region = [left, top, width, height]".to_string())),
+                    };
+                },
+                "density" => {
+                    soup.density = match value {
+                        Value::Float(density) if (0.0..=1.0).contains(&density) => density,
+                        Value::Integer(density) if (0..=1).contains(&density) => density as f64,
+                        _ => return Err(error("expected a density between 0 and 1".to_string())),
+                    }
+                },
+                "symmetry" => soup.symmetry = Symmetry::parse(value.string().map_err(error)?).map_err(error)?,
+                "seed" => soup.seed = Some(value.unsigned().map_err(error)?),
+                _ => return Err(error(format!("unknown soup key '{}'", key))),
+            }
+        }
+        Ok(soup)
+    }
+
+    // A cell of the grid, which positions must fall on
+    fn cell(&self, x: i64, y: i64) -> Result<(usize, usize), String> {
+        if (0..self.width as i64).contains(&x) && (0..self.height as i64).contains(&y) {
+            Ok((x as usize, y as usize))
+        } else {
+            Err(format!("{},{} is off the {}x{} grid", x, y, self.width, self.height))
+        }
+    }
+
+    // The soups of every region for a scenario seed
+    pub fn soups(&self, seed: u64) -> Vec<Soup> {
+        self.soups
+            .iter()
+            .zip(0..)
+            .map(|(soup, i)| Soup { seed: soup.seed.unwrap_or(seed.wrapping_add(i)), density: soup.density, region: soup.region, symmetry: soup.symmetry })
+            .collect()
+    }
+}
+
+// A value of a key = value line
+enum Value {
+    String(String),
+    Integer(i64),
+    Float(f64),
+    Array(Vec<Value>),
+}
+
+impl Value {
+    fn string(&self) -> Result<&str, String> {
+        match self {
+            Value::String(text) => Ok(text),
+            _ => Err("expected a string in quotes".to_string()),
+        }
+    }
+
+    fn unsigned(&self) -> Result<u64, String> {
+        match self {
+            Value::Integer(n) if *n >= 0 => Ok(*n as u64),
+            _ => Err("expected a whole number, 0 or more".to_string()),
+        }
+    }
+
+    fn side(&self) -> Result<usize, String> {
+        match self {
+            Value::Integer(n) if (1..=MAX_GRID_SIDE as i64).contains(n) => Ok(*n as usize),
+            _ => Err(format!("expected a grid side from 1 to {}", MAX_GRID_SIDE)),
+        }
+    }
+
+    fn numbers(&self) -> Result<Vec<i64>, String> {
+        let Value::Array(items) = self else {
+            return Err("expected a list of numbers like [1, 2]".to_string());
+        };
+        items
+            .iter()
+            .map(|item| match item {
+                Value::Integer(n) => Ok(*n),
+                _ => Err("expected a list of numbers like [1, 2]".to_string()),
+            })
+            .collect()
+    }
+}
+
+// Key = value lines grouped by the [[table]] header above them ("" before the
+// first one), with the line numbers of headers and keys. Comments start with #
+// outside strings
+type Tables = Vec<(String, usize, Vec<(usize, String, Value)>)>;
+
+fn parse_tables(text: &str) -> Result<Tables, String> {
+    let mut tables: Tables = vec![(String::new(), 0, Vec::new())];
+    for (number, line) in text.lines().enumerate().map(|(i, line)| (i + 1, line)) {
+        let line = strip_comment(line).trim();
+        if line.is_empty() {
+            continue;
+        }
+        if let Some(name) = line.strip_prefix("[[").and_then(|rest| rest.strip_suffix("]]")) {
+            tables.push((name.trim().to_string(), number, Vec::new()));
+            continue;
+        }
+        if line.starts_with('[') {
+            return Err(format!("line {}: only [[pattern]] and [[soup]] tables are supported", number));
+        }
+        let (key, value) = line.split_once('=').ok_or(format!("line {}: expected key = value", number))?;
+        let (value, rest) = parse_value(value.trim()).map_err(|err| format!("line {}: {}", number, err))?;
+        if !rest.trim().is_empty() {
+            return Err(format!("line {}: unexpected \"{}\" after the value", number, rest.trim()));
+        }
+        if let Some((_, _, entries)) = tables.last_mut() {
+            entries.push((number, key.trim().to_string(), value));
+        }
+    }
+    Ok(tables)
+}
+
+// A line up to a # that isn't inside a string
+fn strip_comment(line: &str) -> &str {
+    let mut quoted = false;
+    let mut escaped = false;
+    for (i, c) in line.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if quoted => escaped = true,
+            '"' => quoted = !quoted,
+            '#' if !quoted => return &line[..i],
+            _ => {},
+        }
+    }
+    line
+}
+
+// The value at the start of some text, and the text after it
+fn parse_value(text: &str) -> Result<(Value, &str), String> {
+    if let Some(rest) = text.strip_prefix('"') {
+        let mut value = String::new();
+        let mut chars = rest.char_indices();
+        while let Some((i, c)) = chars.next() {
+            match c {
+                '"' => return Ok((Value::String(value), &rest[i + 1..])),
+                '\\' => match chars.next() {
+                    Some((_, 'n')) => value.push('\n'),
+                    Some((_, 't')) => value.push('\t'),
+                    Some((_, c @ ('"' | '\\'))) => value.push(c),
+                    _ => return Err("unknown escape in string".to_string()),
+                },
+                c => value.push(c),
+            }
+        }
+        return Err("string without its closing quote".to_string());
+    }
+
+    if let Some(mut rest) = text.strip_prefix('[') {
+        let mut items = Vec::new();
+        loop {
+            rest = rest.trim_start();
+            if let Some(after) = rest.strip_prefix(']') {
+                return Ok((Value::Array(items), after));
+            }
+            let (item, after) = parse_value(rest)?;
+            items.push(item);
+            rest = after.trim_start();
+            match rest.strip_prefix(',') {
+                Some(after) => rest = after,
+                None if rest.starts_with(']') => {},
+                None => return Err("expected , or ] in list".to_string()),
+            }
+        }
+    }
+
+    // Bare words run up to the next separator
+    let end = text.find([',', ']', ' ', '\t']).unwrap_or(text.len());
+    let (word, rest) = text.split_at(end);
+    let number = word.replace('_', "");
+    let value = match word {
+        _ if number.parse::<i64>().is_ok() => Value::Integer(number.parse().unwrap_or_default()),
+        _ if number.parse::<f64>().is_ok() => Value::Float(number.parse().unwrap_or_default()),
+        "" => return Err("missing value".to_string()),
+        _ => return Err(format!("'{}' is not a value (strings go in quotes)", word)),
+    };
+    Ok((value, rest))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The example of Scenario::parse
+    const EXAMPLE: &str = r#"
+width = 120
+height = 80
+rule = "B3/S23"
+topology = "torus"
+seed = 42
+
+[[pattern]]
+name = "glider"          # or file = "gun.rle"
+at = [10, 10]
+transform = "rotate90"
+phase = 2
+category = "spaceship"
+
+[[soup]]
+region = [60, 20, 30, 30]
+density = 0.35
+symmetry = "D4"
+"#;
+
+    fn error(text: &str) -> String {
+        match Scenario::parse(text, (100, 100)) {
+            Ok(_) => panic!("parsed {:?}", text),
+            Err(err) => err,
+        }
+    }
+
+    #[test]
+    fn example_parses() {
+        let scenario = Scenario::parse(EXAMPLE, (100, 100)).unwrap();
+        assert_eq!((scenario.width, scenario.height), (120, 80));
+        assert_eq!(scenario.rule.as_deref(), Some("B3/S23"));
+        assert!(scenario.topology == Topology::Torus);
+        assert_eq!(scenario.seed, Some(42));
+
+        let [placed] = &scenario.patterns[..] else { panic!("expected one pattern") };
+        assert!(matches!(&placed.source, Source::Library(name) if name == "glider"));
+        assert_eq!(placed.at, (10, 10));
+        assert!(placed.orientation == Orientation { transform: Transform::Rotate90, phase: 2 });
+        assert!(placed.category == Some(Category::Spaceship));
+
+        let [soup] = &scenario.soups[..] else { panic!("expected one soup") };
+        assert_eq!(soup.region, (60, 20, 30, 30));
+        assert_eq!(soup.density, 0.35);
+        assert!(soup.symmetry == Symmetry::D4);
+        // A soup without a seed takes the scenario's plus its position
+        assert_eq!(scenario.soups(7)[0].seed, 7);
+    }
+
+    #[test]
+    fn defaults() {
+        let scenario = Scenario::parse("[[soup]]", (50, 40)).unwrap();
+        assert_eq!((scenario.width, scenario.height), (50, 40));
+        assert!(scenario.rule.is_none() && scenario.seed.is_none());
+        assert_eq!(scenario.soups[0].region, (0, 0, 50, 40));
+        assert_eq!(scenario.soups[0].density, DEFAULT_DENSITY);
+    }
+
+    #[test]
+    fn values() {
+        let text = "[[pattern]]\nfile = \"a # b \\\"c\\\".rle\" # comment\nat = [1_0, 2]\n";
+        let scenario = Scenario::parse(text, (100, 100)).unwrap();
+        assert!(matches!(&scenario.patterns[0].source, Source::File(file) if file == "a # b \"c\".rle"));
+        assert_eq!(scenario.patterns[0].at, (10, 2));
+
+        let (value, rest) = parse_value("[[1, 2], [], \"x\"] # after").unwrap();
+        let Value::Array(items) = value else { panic!("expected an array") };
+        assert_eq!(items.len(), 3);
+        assert!(matches!(&items[0], Value::Array(inner) if inner.len() == 2));
+        assert_eq!(rest.trim(), "# after");
+    }
+
+    #[test]
+    fn tables_after_the_size() {
+        // Positions are checked against a size given above or below them, as
+        // long as it is given before the first table
+        let text = "width = 300\n[[pattern]]\nname = \"block\"\nat = [250, 5]\n[[soup]]\nregion = [200, 0, 100, 10]\n";
+        assert!(Scenario::parse(text, (100, 100)).is_ok());
+        assert!(error("[[pattern]]\nname = \"block\"\nwidth = 300").contains("unknown pattern key 'width'"));
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(error("foo = 1"), "line 1: unknown key 'foo'");
+        assert_eq!(error("[[pattern]]\nname = \"glider\"\ncolour = \"red\""), "line 3: unknown pattern key 'colour'");
+        assert_eq!(error("[[soup]]\nsize = 3"), "line 2: unknown soup key 'size'");
+        assert_eq!(error("[[pattern]]\nname = \"glider\"\nat = [100, 5]"), "line 3: 100,5 is off the 100x100 grid");
+        assert_eq!(error("[[pattern]]\nname = \"glider\"\nat = [-1, 5]"), "line 3: -1,5 is off the 100x100 grid");
+        assert_eq!(error("[[soup]]\nregion = [90, 90, 20, 20]"), "line 2: region does not fit the 100x100 grid");
+        assert_eq!(error("rule = \"B3/S23"), "line 1: string without its closing quote");
+        assert_eq!(error("[[pattern]]\nat = [1, 2]"), "line 1: a [[pattern]] needs a name or a file");
+        assert_eq!(error("[[bogus]]"), "line 1: unknown table [[bogus]] (use [[pattern]] or [[soup]])");
+        assert_eq!(error("[pattern]"), "line 1: only [[pattern]] and [[soup]] tables are supported");
+        assert_eq!(error("width = 0"), "line 1: expected a grid side from 1 to 1000");
+        assert_eq!(error("seed = 1 2"), "line 1: unexpected \"2\" after the value");
+        assert_eq!(error("x = [1, 2"), "line 1: expected , or ] in list");
+        assert_eq!(error("x = yes"), "line 1: 'yes' is not a value (strings go in quotes)");
+        assert_eq!(error("[[pattern]]\nfile = \"a.rle\"\nphase = 1001"), "line 3: phase 1001 is past the last one, 1000");
+        assert!(Scenario::parse("[[pattern]]\nname = \"glider\"\nphase = 1000000", (100, 100)).is_ok());
+    }
+
+    // The pattern of a scenario's only [[pattern]] table
+    fn placed(text: &str, library: &Library) -> Result<Pattern, String> {
+        let scenario = Scenario::parse(text, (100, 100)).unwrap();
+        scenario.patterns[0].pattern(library).map(|(pattern, _)| pattern)
+    }
+
+    #[test]
+    fn phases() {
+        let library = Library::builtin();
+        let at_phase = |name: &str, phase: u64| {
+            let entry = library.get(name).unwrap();
+            Orientation { transform: Transform::Identity, phase }.apply(&entry.pattern).unwrap().cells
+        };
+
+        // Oscillators repeat, so any phase is fine
+        let blinker = placed("[[pattern]]\nname = \"blinker\"\nphase = 1000001", &library).unwrap();
+        assert_eq!(blinker.cells, at_phase("blinker", 1));
+        // A gun past its period has fired more gliders than one period in
+        let gun = placed("[[pattern]]\nname = \"gosper-glider-gun\"\nphase = 45", &library).unwrap();
+        assert_eq!(gun.cells, at_phase("gosper-glider-gun", 45));
+        assert_ne!(gun.cells, at_phase("gosper-glider-gun", 15));
+        // A spaceship would move, so its phase is capped like any other
+        let glider = placed("[[pattern]]\nname = \"glider\"\nphase = 1000000", &library);
+        assert!(matches!(glider, Err(error) if error == "phase 1000000 is past the last one, 1000"));
+    }
+}
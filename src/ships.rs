@@ -0,0 +1,167 @@
+use std::collections::VecDeque;
+use crate::census::{Behaviour, SeenObject};
+
+// Past positions kept per ship for its trail
+const TRAIL_LENGTH: usize = 40;
+// A ship may go unseen this many generations, say while debris touches it, before it counts as gone
+const LOST_AFTER: u64 = 8;
+// Furthest a ship's centre may be from where it was expected, in cells, to still be the same ship
+const MATCH_DISTANCE: f64 = 2.5;
+
+// A spaceship followed across generations
+pub struct Ship {
+    pub id: u32,
+    pub period: usize,
+    pub dx: i64,
+    pub dy: i64,
+    pub trail: VecDeque<(f64, f64)>, // Centres on the board, newest first
+    last_seen: u64,
+    contact: Option<(u64, (f64, f64))>, // When and where it vanished into other cells, if it did
+}
+
+impl Ship {
+    pub fn position(&self) -> (f64, f64) {
+        self.trail[0]
+    }
+
+    // Speed as a fraction of c, one cell per generation: c/4, 2c/5, c/2...
+    pub fn speed(&self) -> String {
+        let cells = self.dx.unsigned_abs().max(self.dy.unsigned_abs());
+        let period = self.period as u64;
+        let divisor = gcd(cells, period).max(1);
+        match cells / divisor {
+            1 => format!("c/{}", period / divisor),
+            cells => format!("{}c/{}", cells, period / divisor),
+        }
+    }
+
+    // Compass heading, with y growing downwards; ships that are neither
+    // orthogonal nor diagonal are oblique
+    pub fn heading(&self) -> &'static str {
+        match (self.dx.signum(), self.dy.signum()) {
+            _ if self.dx != 0 && self.dy != 0 && self.dx.abs() != self.dy.abs() => "oblique",
+            (0, -1) => "N",
+            (1, -1) => "NE",
+            (1, 0) => "E",
+            (1, 1) => "SE",
+            (0, 1) => "S",
+            (-1, 1) => "SW",
+            (-1, 0) => "W",
+            (-1, -1) => "NW",
+            _ => "still",
+        }
+    }
+
+    // Where the ship should be by now if it kept going
+    fn expected(&self, generation: u64) -> (f64, f64) {
+        let elapsed = generation.saturating_sub(self.last_seen) as f64;
+        let (x, y) = self.position();
+        (x + self.dx as f64 / self.period as f64 * elapsed, y + self.dy as f64 / self.period as f64 * elapsed)
+    }
+}
+
+fn gcd(a: u64, b: u64) -> u64 {
+    if b == 0 { a } else { gcd(b, a % b) }
+}
+
+// Gives every spaceship on a toroidal board, or a view of the plane, an ID and follows it from one
+// generation to the next, noticing when one is destroyed in a collision
+#[derive(Default)]
+pub struct ShipTracker {
+    ships: Vec<Ship>,
+    next_id: u32,
+    last_generation: Option<u64>,
+}
+
+impl ShipTracker {
+    pub fn ships(&self) -> &[Ship] {
+        &self.ships
+    }
+
+    // Match the spaceships of a new generation to the tracked ones and return a
+    // line for each ship destroyed in a collision. A generation that doesn't
+    // follow the last one starts the tracking over. A grid that doesn't wrap is
+    // a view, and ships crossing its edges leave it
+    pub fn update(&mut self, generation: u64, objects: &[SeenObject], grid: &[Vec<bool>], wraps: bool) -> Vec<String> {
+        if self.last_generation.map(|last| last + 1) != Some(generation) {
+            self.ships.clear();
+            self.next_id = 0;
+        }
+        self.last_generation = Some(generation);
+
+        let height = grid.len() as f64;
+        let width = grid.first().map_or(0, |row| row.len()) as f64;
+        // Distance between two points on the torus
+        let distance = |a: (f64, f64), b: (f64, f64)| {
+            let wrap = |d: f64, size: f64| if wraps { (d + size / 2.0).rem_euclid(size) - size / 2.0 } else { d };
+            wrap(a.0 - b.0, width).hypot(wrap(a.1 - b.1, height))
+        };
+
+        let mut matched = vec![false; self.ships.len()];
+        for object in objects {
+            let Behaviour::Spaceship { period, dx, dy } = object.behaviour else {
+                continue;
+            };
+            let nearest = self
+                .ships
+                .iter()
+                .enumerate()
+                // Ships added this generation are past the end of `matched` and not candidates
+                .filter(|&(i, _)| matched.get(i) == Some(&false))
+                .map(|(i, ship)| (i, distance(ship.expected(generation), object.centre)))
+                .filter(|&(_, d)| d <= MATCH_DISTANCE)
+                .min_by(|a, b| a.1.total_cmp(&b.1));
+
+            let ship = match nearest {
+                Some((i, _)) => {
+                    matched[i] = true;
+                    &mut self.ships[i]
+                },
+                None => {
+                    self.next_id += 1;
+                    self.ships.push(Ship { id: self.next_id, period, dx, dy, trail: VecDeque::new(), last_seen: generation, contact: None });
+                    self.ships.last_mut().expect("a ship was just added")
+                },
+            };
+            (ship.period, ship.dx, ship.dy, ship.last_seen, ship.contact) = (period, dx, dy, generation, None);
+            ship.trail.push_front(object.centre);
+            ship.trail.truncate(TRAIL_LENGTH);
+        }
+
+        // A ship that stops being seen with cells where it should be has run into
+        // something. Ships unseen for too long are dropped, and logged if that was
+        // a collision: a ship that comes back, or just left the view, is not
+        let mut events = Vec::new();
+        for ship in self.ships.iter_mut().filter(|ship| ship.last_seen < generation && ship.contact.is_none()) {
+            let (x, y) = ship.expected(generation);
+            let cells_near = (-3..=3).any(|dy: i64| {
+                (-3..=3).any(|dx: i64| {
+                    let (cx, cy) = (x.round() as i64 + dx, y.round() as i64 + dy);
+                    let (width, height) = (width as i64, height as i64);
+                    if wraps {
+                        grid[cy.rem_euclid(height) as usize][cx.rem_euclid(width) as usize]
+                    } else {
+                        // Past the edge of a view, nothing is seen
+                        (0..width).contains(&cx) && (0..height).contains(&cy) && grid[cy as usize][cx as usize]
+                    }
+                })
+            });
+            if cells_near {
+                ship.contact = Some((generation, (x, y)));
+            }
+        }
+        self.ships.retain(|ship| {
+            if generation - ship.last_seen <= LOST_AFTER {
+                return true;
+            }
+            if let Some((when, (x, y))) = ship.contact {
+                events.push(format!(
+                    "Gen {}: ship {} ({} {}) destroyed in a collision near {},{}",
+                    when, ship.id, ship.speed(), ship.heading(), x.round(), y.round()
+                ));
+            }
+            false
+        });
+        events
+    }
+}
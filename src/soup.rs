@@ -0,0 +1,94 @@
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+// Symmetry imposed on a soup, named as in apgsearch
+#[derive(Clone, Copy, PartialEq)]
+pub enum Symmetry {
+    C1, // No symmetry
+    D2, // Mirrored left to right
+    D4, // Mirrored left to right and top to bottom
+    D8, // All 8 rotations and reflections of a square
+}
+
+impl Symmetry {
+    pub fn parse(name: &str) -> Result<Self, String> {
+        match name.trim().to_ascii_uppercase().as_str() {
+            "C1" => Ok(Symmetry::C1),
+            "D2" => Ok(Symmetry::D2),
+            "D4" => Ok(Symmetry::D4),
+            "D8" => Ok(Symmetry::D8),
+            _ => Err(format!("'{}' is not a symmetry (use C1, D2, D4 or D8)", name)),
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Symmetry::C1 => "C1",
+            Symmetry::D2 => "D2",
+            Symmetry::D4 => "D4",
+            Symmetry::D8 => "D8",
+        }
+    }
+}
+
+// Everything needed to regenerate a random soup exactly
+#[derive(Clone, Copy)]
+pub struct Soup {
+    pub seed: u64,
+    pub density: f64,
+    pub region: (usize, usize, usize, usize), // Left, top, width, height on the grid
+    pub symmetry: Symmetry,
+}
+
+impl Soup {
+    // Live cells of the soup, in grid coordinates. One random number is drawn per
+    // cell of the region in reading order, and every cell copies the draw of the
+    // representative of its orbit under the symmetry, so the same seed always
+    // gives the same soup
+    pub fn cells(&self) -> Vec<(usize, usize)> {
+        let (left, top, mut width, mut height) = self.region;
+        if self.symmetry == Symmetry::D8 {
+            // Diagonal reflections need a square
+            width = width.min(height);
+            height = width;
+        }
+
+        let mut rng = StdRng::seed_from_u64(self.seed);
+        let draws: Vec<bool> = (0..width * height).map(|_| rng.gen_bool(self.density)).collect();
+
+        let mut cells = Vec::new();
+        for y in 0..height {
+            for x in 0..width {
+                let (rx, ry) = self.representative(x, y, width, height);
+                if draws[ry * width + rx] {
+                    cells.push((left + x, top + y));
+                }
+            }
+        }
+        cells
+    }
+
+    // Cell of the region standing for the orbit of (x, y)
+    fn representative(&self, x: usize, y: usize, width: usize, height: usize) -> (usize, usize) {
+        let mirrored_x = x.min(width - 1 - x);
+        let mirrored_y = y.min(height - 1 - y);
+        match self.symmetry {
+            Symmetry::C1 => (x, y),
+            Symmetry::D2 => (mirrored_x, y),
+            Symmetry::D4 => (mirrored_x, mirrored_y),
+            Symmetry::D8 => (mirrored_x.min(mirrored_y), mirrored_x.max(mirrored_y)),
+        }
+    }
+}
+
+// Parse a soup region written "left,top,width,height"
+pub fn parse_region(spec: &str) -> Result<(usize, usize, usize, usize), String> {
+    let numbers: Vec<usize> = spec
+        .split(',')
+        .map(|n| n.trim().parse::<usize>().map_err(|_| format!("'{}' is not a cell count", n)))
+        .collect::<Result<_, _>>()?;
+    match numbers[..] {
+        [left, top, width, height] if width > 0 && height > 0 => Ok((left, top, width, height)),
+        _ => Err(format!("region '{}' should be left,top,width,height with a non-empty size", spec)),
+    }
+}
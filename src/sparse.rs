@@ -0,0 +1,243 @@
+use std::collections::{HashMap, HashSet};
+
+// Side of a square chunk; each chunk row fits in a u64 bitmask
+const CHUNK_SIZE: i64 = 64;
+
+// A 64x64 tile of cells, one bit per cell (bit x of rows[y] is cell (x, y))
+#[derive(Clone, Copy)]
+struct Chunk {
+    rows: [u64; CHUNK_SIZE as usize],
+}
+
+impl Chunk {
+    fn empty() -> Self {
+        Chunk { rows: [0; CHUNK_SIZE as usize] }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.rows.iter().all(|&row| row == 0)
+    }
+
+    fn population(&self) -> u64 {
+        self.rows.iter().map(|row| row.count_ones() as u64).sum()
+    }
+}
+
+// Unbounded Game of Life board that only stores chunks containing live cells.
+// Chunks are created as patterns grow into them and dropped once they empty out.
+pub struct SparseBoard {
+    chunks: HashMap<(i64, i64), Chunk>,
+}
+
+impl SparseBoard {
+    pub fn new() -> Self {
+        SparseBoard { chunks: HashMap::new() }
+    }
+
+    // Build a board from the dense grid, with grid cell (0, 0) at the origin
+    pub fn from_grid(grid: &[Vec<bool>]) -> Self {
+        let mut board = SparseBoard::new();
+        for (y, row) in grid.iter().enumerate() {
+            for (x, &alive) in row.iter().enumerate() {
+                if alive {
+                    board.set(x as i64, y as i64, true);
+                }
+            }
+        }
+        board
+    }
+
+    pub fn get(&self, x: i64, y: i64) -> bool {
+        let (key, lx, ly) = split(x, y);
+        self.chunks
+            .get(&key)
+            .is_some_and(|chunk| chunk.rows[ly] >> lx & 1 == 1)
+    }
+
+    pub fn set(&mut self, x: i64, y: i64, alive: bool) {
+        let (key, lx, ly) = split(x, y);
+        if alive {
+            self.chunks.entry(key).or_insert_with(Chunk::empty).rows[ly] |= 1 << lx;
+        } else if let Some(chunk) = self.chunks.get_mut(&key) {
+            chunk.rows[ly] &= !(1 << lx);
+            if chunk.is_empty() {
+                self.chunks.remove(&key);
+            }
+        }
+    }
+
+    // Dense view of the board through a camera whose top-left cell is (left, top)
+    pub fn window(&self, left: i64, top: i64, width: usize, height: usize) -> Vec<Vec<bool>> {
+        let mut grid = vec![vec![false; width]; height];
+        for (y, row) in grid.iter_mut().enumerate() {
+            for (x, cell) in row.iter_mut().enumerate() {
+                *cell = self.get(left + x as i64, top + y as i64);
+            }
+        }
+        grid
+    }
+
+    // Positions of all live cells, in no particular order
+    pub fn cells(&self) -> Vec<(i64, i64)> {
+        let mut cells = Vec::new();
+        for (&(cx, cy), chunk) in &self.chunks {
+            for (y, &row) in chunk.rows.iter().enumerate() {
+                let bits = (0..CHUNK_SIZE).filter(|x| row >> x & 1 == 1);
+                cells.extend(bits.map(|x| (cx * CHUNK_SIZE + x, cy * CHUNK_SIZE + y as i64)));
+            }
+        }
+        cells
+    }
+
+    pub fn population(&self) -> u64 {
+        self.chunks.values().map(Chunk::population).sum()
+    }
+
+    pub fn chunk_count(&self) -> usize {
+        self.chunks.len()
+    }
+
+    // Advance one generation. Only live chunks and their neighbours can hold
+    // cells next generation, and chunks that end up empty are not kept
+    pub fn step(&mut self) {
+        let mut candidates = HashSet::new();
+        for &(cx, cy) in self.chunks.keys() {
+            for dy in -1..=1 {
+                for dx in -1..=1 {
+                    candidates.insert((cx + dx, cy + dy));
+                }
+            }
+        }
+
+        let mut next = HashMap::new();
+        for key in candidates {
+            let chunk = self.step_chunk(key);
+            if !chunk.is_empty() {
+                next.insert(key, chunk);
+            }
+        }
+        self.chunks = next;
+    }
+
+    fn step_chunk(&self, (cx, cy): (i64, i64)) -> Chunk {
+        let mut chunk = Chunk::empty();
+        for y in 0..CHUNK_SIZE {
+            let above = self.row_window(cx, cy, y - 1);
+            let here = self.row_window(cx, cy, y);
+            let below = self.row_window(cx, cy, y + 1);
+
+            let mut row = 0u64;
+            for x in 0..CHUNK_SIZE as u32 {
+                // Bit x + 1 of a window is cell x, so the 3 bits starting at x are the neighbourhood
+                let live_neighbors = ((above >> x) & 0b111).count_ones()
+                    + ((here >> x) & 0b101).count_ones()
+                    + ((below >> x) & 0b111).count_ones();
+                let alive = (here >> (x + 1)) & 1 == 1;
+                if live_neighbors == 3 || (alive && live_neighbors == 2) {
+                    row |= 1 << x;
+                }
+            }
+            chunk.rows[y as usize] = row;
+        }
+        chunk
+    }
+
+    // Row y of chunk (cx, cy) widened by one cell on each side: bit 0 is the last
+    // cell of the chunk to the left, bits 1..=64 the row itself and bit 65 the
+    // first cell of the chunk to the right. y may spill into the chunks above/below
+    fn row_window(&self, cx: i64, cy: i64, y: i64) -> u128 {
+        let cy = cy + y.div_euclid(CHUNK_SIZE);
+        let ly = y.rem_euclid(CHUNK_SIZE) as usize;
+        let row = |cx: i64| self.chunks.get(&(cx, cy)).map_or(0, |chunk| chunk.rows[ly]);
+
+        let left = (row(cx - 1) >> (CHUNK_SIZE - 1)) as u128;
+        let middle = (row(cx) as u128) << 1;
+        let right = ((row(cx + 1) & 1) as u128) << (CHUNK_SIZE + 1);
+        left | middle | right
+    }
+}
+
+// Chunk key and position inside the chunk for a cell
+fn split(x: i64, y: i64) -> ((i64, i64), usize, usize) {
+    (
+        (x.div_euclid(CHUNK_SIZE), y.div_euclid(CHUNK_SIZE)),
+        x.rem_euclid(CHUNK_SIZE) as usize,
+        y.rem_euclid(CHUNK_SIZE) as usize,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // One Life generation of a set of cells, neighbour by neighbour
+    fn life_step(cells: &HashSet<(i64, i64)>) -> HashSet<(i64, i64)> {
+        let mut counts: HashMap<(i64, i64), u32> = HashMap::new();
+        for &(x, y) in cells {
+            for dy in -1..=1 {
+                for dx in -1..=1 {
+                    if (dx, dy) != (0, 0) {
+                        *counts.entry((x + dx, y + dy)).or_default() += 1;
+                    }
+                }
+            }
+        }
+        counts.into_iter().filter(|&(cell, n)| n == 3 || (n == 2 && cells.contains(&cell))).map(|(cell, _)| cell).collect()
+    }
+
+    // Run a pattern on the board and on the brute-force step side by side
+    fn runs_like_life(start: &[(i64, i64)], generations: usize) {
+        let mut board = SparseBoard::new();
+        for &(x, y) in start {
+            board.set(x, y, true);
+        }
+        let mut expected: HashSet<(i64, i64)> = start.iter().copied().collect();
+        for generation in 1..=generations {
+            board.step();
+            expected = life_step(&expected);
+            assert_eq!(board.cells().into_iter().collect::<HashSet<_>>(), expected, "generation {}", generation);
+        }
+    }
+
+    #[test]
+    fn blinkers_across_chunk_edges() {
+        // Horizontal over x = 63/64, and vertical over y = 63/64
+        runs_like_life(&[(62, 10), (63, 10), (64, 10)], 4);
+        runs_like_life(&[(10, 62), (10, 63), (10, 64)], 4);
+        // And over both at once, next to the chunk corner
+        runs_like_life(&[(63, 62), (63, 63), (63, 64), (64, 63)], 10);
+    }
+
+    #[test]
+    fn patterns_around_the_origin() {
+        // An R-pentomino centred on the origin spreads into all four quadrants
+        runs_like_life(&[(0, -1), (1, -1), (-1, 0), (0, 0), (0, 1)], 60);
+        // A glider heading up and left, into negative chunks
+        runs_like_life(&[(1, 2), (0, 1), (2, 0), (1, 0), (0, 0)], 20);
+        assert!(!SparseBoard::new().get(-1, -1));
+        let mut board = SparseBoard::new();
+        board.set(-1, -64, true);
+        assert_eq!(board.cells(), vec![(-1, -64)]);
+        assert_eq!(board.window(-2, -65, 3, 3), vec![vec![false; 3], vec![false, true, false], vec![false; 3]]);
+    }
+
+    #[test]
+    fn empty_chunks_are_dropped() {
+        let mut board = SparseBoard::new();
+        // A block that stays, and a lone cell far away that dies
+        for (x, y) in [(0, 0), (1, 0), (0, 1), (1, 1), (500, -500)] {
+            board.set(x, y, true);
+        }
+        assert_eq!(board.chunk_count(), 2);
+        board.step();
+        assert_eq!(board.chunk_count(), 1);
+        assert_eq!(board.population(), 4);
+
+        board.set(0, 0, false);
+        board.set(1, 0, false);
+        board.set(0, 1, false);
+        assert_eq!(board.chunk_count(), 1);
+        board.set(1, 1, false);
+        assert_eq!(board.chunk_count(), 0);
+    }
+}
@@ -0,0 +1,62 @@
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+// Noise applied on top of a deterministic step: a birth only happens with
+// probability `birth`, a survival with probability `survival`, and any cell that
+// would stay dead is born anyway with probability `spontaneous`.
+// The RNG is seeded, so the same seed and board replay the same run
+pub struct StochasticRule {
+    pub birth: f64,
+    pub survival: f64,
+    pub spontaneous: f64,
+    seed: u64,
+    rng: StdRng,
+}
+
+impl StochasticRule {
+    pub fn new(birth: f64, survival: f64, spontaneous: f64, seed: u64) -> Self {
+        StochasticRule { birth, survival, spontaneous, seed, rng: StdRng::seed_from_u64(seed) }
+    }
+
+    // Parse probabilities like "b=0.9,s=0.95,n=0.001"; missing ones default to
+    // certain births and survivals and no spontaneous births
+    pub fn parse(spec: &str, seed: u64) -> Result<Self, String> {
+        let mut rule = StochasticRule::new(1.0, 1.0, 0.0, seed);
+        for field in spec.trim().split(',') {
+            let (key, value) = field.split_once('=').ok_or(format!("'{}' should look like key=value", field))?;
+            let probability = match value.trim().parse::<f64>() {
+                Ok(p) if (0.0..=1.0).contains(&p) => p,
+                _ => return Err(format!("'{}' is not a probability between 0 and 1", value)),
+            };
+            match key.trim() {
+                "b" => rule.birth = probability,
+                "s" => rule.survival = probability,
+                "n" => rule.spontaneous = probability,
+                _ => return Err(format!("unknown probability '{}' (use b, s or n)", key)),
+            }
+        }
+        Ok(rule)
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    // Start the random sequence over, so a reset replays the same run
+    pub fn reseed(&mut self) {
+        self.rng = StdRng::seed_from_u64(self.seed);
+    }
+
+    // Whether a cell is alive next generation, given whether it is alive now and
+    // what the deterministic rule decided. Only draws when a probability is in
+    // play, so the sequence depends on the board alone
+    pub fn apply(&mut self, was_alive: bool, alive: bool) -> bool {
+        let chance = match (was_alive, alive) {
+            (false, true) => self.birth,
+            (true, true) => self.survival,
+            (false, false) => return self.spontaneous > 0.0 && self.rng.gen_bool(self.spontaneous),
+            (true, false) => return false,
+        };
+        chance >= 1.0 || self.rng.gen_bool(chance)
+    }
+}
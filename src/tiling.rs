@@ -0,0 +1,134 @@
+// Shape of the cells on screen
+#[derive(Clone, Copy, PartialEq)]
+pub enum Tiling {
+    Square,
+    Hexagonal,  // Pointy-top hexagons, odd rows shifted half a cell right (matches Neighborhood::Hexagonal)
+    Triangular, // Alternating up/down triangles, cell (x, y) points up when x + y is even
+}
+
+impl Tiling {
+    pub fn next(self) -> Tiling {
+        match self {
+            Tiling::Square => Tiling::Hexagonal,
+            Tiling::Hexagonal => Tiling::Triangular,
+            Tiling::Triangular => Tiling::Square,
+        }
+    }
+}
+
+const SQRT_3: f32 = 1.732_050_8;
+
+// Where a columns x rows board sits on screen for a given tiling.
+// size is the side of a square, the radius of a hexagon or the side of a triangle
+pub struct Layout {
+    pub tiling: Tiling,
+    pub left: f32,
+    pub top: f32,
+    pub size: f32,
+    pub columns: usize,
+    pub rows: usize,
+}
+
+impl Layout {
+    // Fit the board, centred, inside a width x height area leaving a border on every side
+    pub fn new(tiling: Tiling, columns: usize, rows: usize, width: u32, height: u32, border: u32) -> Self {
+        let inner_width = width.saturating_sub(border * 2) as f32;
+        let inner_height = height.saturating_sub(border * 2) as f32;
+        let (cols, rows_f) = (columns as f32, rows as f32);
+
+        let size = match tiling {
+            // Whole pixels keep square cells crisp
+            Tiling::Square => (inner_width / cols).min(inner_height / rows_f).floor().max(1.0),
+            Tiling::Hexagonal => (inner_width / (SQRT_3 * (cols + 0.5))).min(inner_height / (1.5 * rows_f + 0.5)),
+            Tiling::Triangular => (inner_width / ((cols + 1.0) / 2.0)).min(inner_height / (rows_f * SQRT_3 / 2.0)),
+        }
+        .max(1.0);
+
+        let mut layout = Layout { tiling, left: 0.0, top: 0.0, size, columns, rows };
+        let (board_width, board_height) = layout.pixel_size();
+        layout.left = ((width as f32 - board_width) / 2.0).floor();
+        layout.top = ((height as f32 - board_height) / 2.0).floor();
+        layout
+    }
+
+    // Width and height of the whole board in pixels
+    pub fn pixel_size(&self) -> (f32, f32) {
+        let (cols, rows) = (self.columns as f32, self.rows as f32);
+        match self.tiling {
+            Tiling::Square => (cols * self.size, rows * self.size),
+            Tiling::Hexagonal => (SQRT_3 * self.size * (cols + 0.5), self.size * (1.5 * rows + 0.5)),
+            Tiling::Triangular => ((cols + 1.0) * self.size / 2.0, rows * self.size * SQRT_3 / 2.0),
+        }
+    }
+
+    // Screen position of the centre of a cell
+    pub fn cell_centre(&self, column: usize, row: usize) -> (f32, f32) {
+        let (x, y) = (column as f32, row as f32);
+        match self.tiling {
+            Tiling::Square => (self.left + (x + 0.5) * self.size, self.top + (y + 0.5) * self.size),
+            Tiling::Hexagonal => {
+                let shift = if row % 2 == 1 { 0.5 } else { 0.0 };
+                (self.left + SQRT_3 * self.size * (x + 0.5 + shift), self.top + self.size * (1.0 + 1.5 * y))
+            },
+            Tiling::Triangular => {
+                // The centroid sits a third of the way up from the base
+                let height = self.size * SQRT_3 / 2.0;
+                let fy = if (column + row).is_multiple_of(2) { 2.0 / 3.0 } else { 1.0 / 3.0 };
+                (self.left + (x + 1.0) * self.size / 2.0, self.top + (y + fy) * height)
+            },
+        }
+    }
+
+    // Cell under a screen position, if any. Rendering uses this too, so what is
+    // drawn and what the mouse edits always agree
+    pub fn pick(&self, screen_x: f32, screen_y: f32) -> Option<(usize, usize)> {
+        let (px, py) = (screen_x - self.left, screen_y - self.top);
+        let (column, row) = match self.tiling {
+            Tiling::Square => ((px / self.size).floor() as i64, (py / self.size).floor() as i64),
+            Tiling::Hexagonal => self.pick_hexagon(px, py),
+            Tiling::Triangular => self.pick_triangle(px, py),
+        };
+        if px < 0.0 || py < 0.0 || column < 0 || row < 0 || column >= self.columns as i64 || row >= self.rows as i64 {
+            return None;
+        }
+        Some((column as usize, row as usize))
+    }
+
+    fn pick_hexagon(&self, px: f32, py: f32) -> (i64, i64) {
+        // Relative to the centre of cell (0, 0), then to fractional axial coordinates
+        let x = px - SQRT_3 * self.size / 2.0;
+        let y = py - self.size;
+        let q = (SQRT_3 / 3.0 * x - y / 3.0) / self.size;
+        let r = (2.0 / 3.0 * y) / self.size;
+
+        // Round in cube coordinates, fixing the component with the largest error
+        let s = -q - r;
+        let (mut rq, mut rr, rs) = (q.round(), r.round(), s.round());
+        let (dq, dr, ds) = ((rq - q).abs(), (rr - r).abs(), (rs - s).abs());
+        if dq > dr && dq > ds {
+            rq = -rr - rs;
+        } else if dr > ds {
+            rr = -rq - rs;
+        }
+
+        // Axial back to offset coordinates with odd rows shifted right
+        let (q, r) = (rq as i64, rr as i64);
+        (q + (r - (r & 1)) / 2, r)
+    }
+
+    fn pick_triangle(&self, px: f32, py: f32) -> (i64, i64) {
+        let height = self.size * SQRT_3 / 2.0;
+        let row = (py / height).floor();
+        let fy = py / height - row;
+        let u = px / (self.size / 2.0);
+        let column = u.floor();
+        let fx = u - column;
+
+        // Each half-triangle-wide strip is split by one slanted edge between
+        // triangle `column` (to its right) and `column - 1` (to its left)
+        let (column, row) = (column as i64, row as i64);
+        let points_up = (column + row) % 2 == 0;
+        let right_of_edge = if points_up { fx + fy >= 1.0 } else { fx >= fy };
+        (if right_of_edge { column } else { column - 1 }, row)
+    }
+}
@@ -0,0 +1,162 @@
+use crate::pattern::{self, Pattern};
+use crate::sparse::SparseBoard;
+
+// Most generations a pattern is run to reach a phase; a periodic pattern can be
+// run its phase modulo its period instead
+pub const MAX_PHASE: u64 = 1000;
+
+// One of the 8 symmetries of the square, as seen on screen (y grows downwards)
+#[derive(Clone, Copy, PartialEq, Default)]
+pub enum Transform {
+    #[default]
+    Identity,
+    Rotate90,       // Clockwise
+    Rotate180,
+    Rotate270,      // Counter-clockwise
+    FlipHorizontal, // Mirrored left to right
+    FlipVertical,   // Mirrored top to bottom
+    Transpose,      // Mirrored across the top-left to bottom-right diagonal
+    AntiTranspose,  // Mirrored across the other diagonal
+}
+
+const ALL: [Transform; 8] = [
+    Transform::Identity,
+    Transform::Rotate90,
+    Transform::Rotate180,
+    Transform::Rotate270,
+    Transform::FlipHorizontal,
+    Transform::FlipVertical,
+    Transform::Transpose,
+    Transform::AntiTranspose,
+];
+
+impl Transform {
+    // Names as written by name(), or Golly's rot90, flip_x and the like
+    pub fn parse(name: &str) -> Result<Self, String> {
+        match name.trim().to_ascii_lowercase().replace('_', "-").as_str() {
+            "identity" | "none" => Ok(Transform::Identity),
+            "rotate90" | "rot90" | "cw" => Ok(Transform::Rotate90),
+            "rotate180" | "rot180" => Ok(Transform::Rotate180),
+            "rotate270" | "rot270" | "ccw" => Ok(Transform::Rotate270),
+            "flip-h" | "flip-x" => Ok(Transform::FlipHorizontal),
+            "flip-v" | "flip-y" => Ok(Transform::FlipVertical),
+            "transpose" | "swap-xy" => Ok(Transform::Transpose),
+            "anti-transpose" | "swap-xy-flip" => Ok(Transform::AntiTranspose),
+            _ => Err(format!("'{}' is not a transform (use identity, rotate90, rotate180, rotate270, flip-h, flip-v, transpose or anti-transpose)", name)),
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Transform::Identity => "identity",
+            Transform::Rotate90 => "rotate90",
+            Transform::Rotate180 => "rotate180",
+            Transform::Rotate270 => "rotate270",
+            Transform::FlipHorizontal => "flip-h",
+            Transform::FlipVertical => "flip-v",
+            Transform::Transpose => "transpose",
+            Transform::AntiTranspose => "anti-transpose",
+        }
+    }
+
+    // [a, b, c, d] moving cell (x, y) to (a x + b y, c x + d y)
+    fn matrix(self) -> [i64; 4] {
+        match self {
+            Transform::Identity => [1, 0, 0, 1],
+            Transform::Rotate90 => [0, -1, 1, 0],
+            Transform::Rotate180 => [-1, 0, 0, -1],
+            Transform::Rotate270 => [0, 1, -1, 0],
+            Transform::FlipHorizontal => [-1, 0, 0, 1],
+            Transform::FlipVertical => [1, 0, 0, -1],
+            Transform::Transpose => [0, 1, 1, 0],
+            Transform::AntiTranspose => [0, -1, -1, 0],
+        }
+    }
+
+    // This transform followed by another one
+    pub fn then(self, next: Transform) -> Transform {
+        let [a, b, c, d] = self.matrix();
+        let [e, f, g, h] = next.matrix();
+        let product = [e * a + f * c, e * b + f * d, g * a + h * c, g * b + h * d];
+        ALL.into_iter().find(|transform| transform.matrix() == product).unwrap_or_default()
+    }
+
+    // The pattern's whole rectangle transformed, dead margins included
+    pub fn apply(self, pattern: &Pattern) -> Pattern {
+        let [a, b, c, d] = self.matrix();
+        let (width, height) = (pattern.width() as i64, pattern.height() as i64);
+        let (new_width, new_height) = if a == 0 { (height, width) } else { (width, height) };
+        // Shift the corners that land on negative coordinates back to 0
+        let left = a.min(0) * (width - 1) + b.min(0) * (height - 1);
+        let top = c.min(0) * (width - 1) + d.min(0) * (height - 1);
+
+        let mut cells = vec![vec![0; new_width.max(0) as usize]; new_height.max(0) as usize];
+        for (y, row) in pattern.cells.iter().enumerate() {
+            for (x, &state) in row.iter().enumerate() {
+                let (x, y) = (x as i64, y as i64);
+                cells[(c * x + d * y - top) as usize][(a * x + b * y - left) as usize] = state;
+            }
+        }
+        Pattern { cells, ..pattern.clone() }
+    }
+}
+
+// A Life pattern some generations later, cut down to its live cells
+pub fn advance(pattern: &Pattern, generations: u64) -> Result<Pattern, String> {
+    if generations == 0 {
+        return Ok(pattern.clone());
+    }
+    if generations > MAX_PHASE {
+        return Err(format!("phase {} is past the last one, {}", generations, MAX_PHASE));
+    }
+    if !pattern::is_life(pattern.rule.as_deref()) || pattern.cells.iter().flatten().any(|&state| state > 1) {
+        return Err("only Life patterns can be advanced to another phase".to_string());
+    }
+
+    let mut board = SparseBoard::new();
+    for (y, row) in pattern.cells.iter().enumerate() {
+        for (x, _) in row.iter().enumerate().filter(|(_, state)| **state != 0) {
+            board.set(x as i64, y as i64, true);
+        }
+    }
+    for _ in 0..generations {
+        board.step();
+    }
+    let points: Vec<(i64, i64, u8)> = board.cells().into_iter().map(|(x, y)| (x, y, 1)).collect();
+    Ok(Pattern { cells: Pattern::from_points(&points)?.cells, ..pattern.clone() })
+}
+
+// How a pattern is put down: advanced to a phase, then transformed
+#[derive(Clone, Copy, PartialEq, Default)]
+pub struct Orientation {
+    pub transform: Transform,
+    pub phase: u64, // Generations the pattern is run before placing it
+}
+
+impl Orientation {
+    pub fn apply(&self, pattern: &Pattern) -> Result<Pattern, String> {
+        Ok(self.transform.apply(&advance(pattern, self.phase)?))
+    }
+
+    pub fn describe(&self) -> String {
+        match (self.transform, self.phase) {
+            (Transform::Identity, 0) => "as loaded".to_string(),
+            (transform, 0) => transform.name().to_string(),
+            (Transform::Identity, phase) => format!("phase {}", phase),
+            (transform, phase) => format!("{}, phase {}", transform.name(), phase),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn phases_are_capped() {
+        let glider = Pattern { cells: vec![vec![0, 1, 0], vec![0, 0, 1], vec![1, 1, 1]], ..Pattern::default() };
+        // Four generations on, a glider has the shape it started with
+        assert_eq!(advance(&glider, 4).unwrap().cells, glider.cells);
+        assert!(advance(&glider, MAX_PHASE + 1).is_err());
+    }
+}